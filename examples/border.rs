@@ -0,0 +1,25 @@
+//!
+//! Draw a border around the menu with the name of the menu as the title.
+//!
+
+fn main() {
+    use terminal_menu::{menu, label, button, list, submenu, back_button, run, mut_menu, Border};
+    let menu = menu(vec![
+        label("borders"),
+        list("Style", vec!["Rounded", "Single", "Double", "Ascii"]),
+        submenu("Settings", vec![
+            label("the title shows which submenu you are in"),
+            back_button("Back")
+        ]),
+        button("Exit")
+    ]);
+
+    // border, padding and margin are used by submenus too
+    {
+        let mut mm = mut_menu(&menu);
+        mm.set_border(Border::Rounded);
+        mm.set_padding(1);
+        mm.set_margin(1);
+    }
+    run(&menu);
+}
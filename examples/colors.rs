@@ -6,7 +6,7 @@ fn main() {
     use terminal_menu::*;
 
     // see the crossterm crate for all the color options
-    use crossterm::style::Color;
+    use crossterm::style::{Color, Stylize};
 
     let menu = menu(vec![
 
@@ -15,7 +15,11 @@ fn main() {
         label("Green").colorize(Color::Green),
         label("Blue").colorize(Color::Blue),
 
-        // selected item is always cyan
+        // style parts of a name separately
+        label("").styled(vec!["Bold".bold(), " and ".stylize(), "dim".dim()]),
+        button("Magenta").styled(vec!["Magenta".magenta(), " (on yellow)".on_yellow()]),
+
+        // selected item is cyan unless the menu has a theme (see theme.rs)
         button("Cyan")
     ]);
     run(&menu);
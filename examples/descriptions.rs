@@ -0,0 +1,26 @@
+//!
+//! Describe items without cluttering the menu.
+//!
+
+fn main() {
+    use terminal_menu::{menu, label, button, scroll, numeric, string, run, mut_menu};
+    let menu = menu(vec![
+        label("server setup"),
+
+        // the description of the selected item is shown below the items
+        string("Address", "0.0.0.0", false)
+            .describe("Interface the server listens on. Use 127.0.0.1 to only accept connections from this machine."),
+        numeric("Port", 8080.0, Some(1.0), Some(1.0), Some(65535.0))
+            .describe("Port the server listens on. Ports below 1024 need root privileges on most systems."),
+        scroll("Log level", vec!["warn", "info", "debug"])
+            .describe("How much is written to the log.\nDebug logs can get large quickly."),
+
+        button("Save")
+    ]);
+    run(&menu);
+    {
+        let mm = mut_menu(&menu);
+        println!("{}:{}", mm.selection_value("Address"), mm.numeric_value("Port"));
+        println!("{}", mm.selection_value("Log level"));
+    }
+}
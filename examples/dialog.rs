@@ -0,0 +1,28 @@
+//!
+//! Center a small menu on the screen so it looks like a dialog.
+//!
+
+fn main() {
+    use terminal_menu::{menu, label, button, scroll, numeric, run, mut_menu, Border, ValueAlignment};
+    let menu = menu(vec![
+        label("New game"),
+        scroll("Difficulty", vec!["easy", "normal", "hard"]),
+        numeric("Players", 2.0, Some(1.0), Some(1.0), Some(4.0)),
+        button("Start")
+    ]);
+
+    // center the menu horizontally and vertically
+    mut_menu(&menu).set_border(Border::Rounded);
+    mut_menu(&menu).set_padding(1);
+    mut_menu(&menu).set_centered(true, true);
+
+    // values at the right edge of a menu at most 30 columns wide
+    mut_menu(&menu).set_max_width(30);
+    mut_menu(&menu).set_value_alignment(ValueAlignment::Right);
+
+    run(&menu);
+    {
+        let mm = mut_menu(&menu);
+        println!("{} with {} players", mm.selection_value("Difficulty"), mm.numeric_value("Players"));
+    }
+}
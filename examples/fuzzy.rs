@@ -0,0 +1,34 @@
+//!
+//! Filter the items with fuzzy matching, best matches first. (run this example yourself)
+//!
+
+fn main() {
+    use terminal_menu::{menu, button, scroll, run, mut_menu, fuzzy_rank, KeyMap};
+
+    let commands = vec![
+        "Open File", "Open Folder", "Open Recent", "Save", "Save As", "Save All",
+        "Close Editor", "Close Folder", "Toggle Sidebar", "Toggle Terminal",
+        "Find", "Find in Files", "Replace", "Replace in Files", "Go to Line", "Go to Symbol",
+    ];
+    let languages = vec!["Plain Text", "Rust", "Python", "JavaScript", "TypeScript", "Markdown", "TOML", "YAML"];
+
+    let mut items = vec![
+        // enter opens the values, type there to filter them too
+        scroll("Language", languages.clone())
+    ];
+    items.extend(commands.iter().map(|a| button(*a)));
+    let menu = menu(items);
+
+    // type to filter, for example "ofl" finds Open Folder
+    mut_menu(&menu).set_filter(true);
+    mut_menu(&menu).set_keymap(KeyMap::arrows()).unwrap();
+    mut_menu(&menu).set_help_bar(true);
+
+    run(&menu);
+    let menu = mut_menu(&menu);
+    println!("{} ({})", menu.selected_item_name(), menu.selection_value("Language"));
+
+    // the same ranking works on anything
+    let ranked = fuzzy_rank("ts", &languages);
+    println!("ts: {:?}", ranked.iter().map(|a| languages[a.0]).collect::<Vec<_>>());
+}
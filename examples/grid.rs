@@ -0,0 +1,18 @@
+//!
+//! Flow many short items into columns.
+//!
+
+fn main() {
+    use terminal_menu::{menu, label, button, run, mut_menu};
+    let mut items = vec![label("pick a region:")];
+
+    // grid:
+    //  up and down move inside a column
+    //  left and right move between columns
+    items.extend((1..=60).map(|n| button(format!("eu-{:02}", n))));
+    let menu = menu(items);
+    mut_menu(&menu).set_grid(true);
+    run(&menu);
+
+    println!("{}", mut_menu(&menu).selected_item_name());
+}
@@ -0,0 +1,29 @@
+//!
+//! Jump to items with hotkeys. (run this example yourself)
+//!
+
+fn main() {
+    use terminal_menu::{menu, label, button, back_button, scroll, run, mut_menu, KeyMap};
+    let menu = menu(vec![
+        label("press the underlined key"),
+
+        // & marks the hotkey, it selects the item
+        scroll("&Format", vec!["png", "jpeg", "webp"]).mnemonic(),
+
+        // hotkeys press buttons right away
+        button("&Save").mnemonic(),
+        button("Save &as").mnemonic(),
+        button("Export").hotkey('x'),
+        back_button("&Quit").mnemonic()
+    ]);
+
+    // hotkeys can't be keys of the keymap, the default keys include wasd and q
+    if let Err(error) = mut_menu(&menu).set_keymap(KeyMap::arrows()) {
+        eprintln!("{}", error);
+        return;
+    }
+
+    run(&menu);
+    let menu = mut_menu(&menu);
+    println!("{} ({})", menu.selected_item_name(), menu.selection_value("Format"));
+}
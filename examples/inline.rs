@@ -0,0 +1,21 @@
+//!
+//! Draw a small menu under the cursor instead of taking over the whole terminal.
+//!
+
+fn main() {
+    use terminal_menu::{menu, button, list, run, mut_menu, PrintMode};
+    println!("everything above the menu stays visible");
+    let menu = menu(vec![
+        list("Size", vec!["Small", "Medium", "Large"]),
+        button("Order"),
+        button("Cancel")
+    ]);
+
+    // inline:
+    //  the menu erases itself on exit
+    //  with summary the selected item is left on screen
+    mut_menu(&menu).set_print_mode(PrintMode::Inline { summary: true });
+    run(&menu);
+
+    println!("{}", mut_menu(&menu).selection_value("Size"));
+}
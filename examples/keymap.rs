@@ -0,0 +1,22 @@
+//!
+//! Choose the keys of a menu.
+//!
+
+fn main() {
+    use terminal_menu::{menu, label, button, scroll, run, mut_menu, KeyMap, Action};
+    use crossterm::event::KeyCode;
+    let menu = menu(vec![
+        label("ctrl+p and ctrl+n to move, ctrl+g or x to cancel"),
+        scroll("Editor", vec!["emacs", "vim", "nano"]),
+        button("Save"),
+        button("Quit")
+    ]);
+
+    // start from a preset: KeyMap::arrows(), KeyMap::vim() or KeyMap::emacs()
+    // and add keys of your own
+    mut_menu(&menu).set_keymap(KeyMap::emacs().bind(KeyCode::Char('x'), Action::Cancel)).unwrap();
+    mut_menu(&menu).set_help_bar(true);
+
+    run(&menu);
+    println!("{}", mut_menu(&menu).selected_item_name());
+}
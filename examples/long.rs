@@ -3,13 +3,22 @@
 ///
 
 fn main() {
-    use terminal_menu::{menu, button, run, mut_menu};
+    use terminal_menu::{menu, button, run, mut_menu, KeyMap};
     let menu = menu(
 
         // create buttons representing numbers from 1 to 100
         (1..100).map(|n| button(format!("{}", n))).collect()
 
     );
+
+    // show how far the list has been scrolled
+    mut_menu(&menu).set_scrollbar(true);
+    mut_menu(&menu).set_position_counter(true);
+
+    // type to show only the matching numbers, esc to show all of them again
+    mut_menu(&menu).set_filter(true);
+    mut_menu(&menu).set_keymap(KeyMap::arrows()).unwrap();
+
     run(&menu);
     println!("{}", mut_menu(&menu).selected_item_name());
-}
\ No newline at end of file
+}
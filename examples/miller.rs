@@ -0,0 +1,33 @@
+//!
+//! Browse nested submenus in miller columns, like the ranger file manager.
+//!
+
+fn main() {
+    use terminal_menu::{menu, submenu, button, back_button, run, mut_menu};
+    let menu = menu(vec![
+        submenu("Fruits", vec![
+            submenu("Apples", vec![
+                button("Fuji"),
+                button("Gala"),
+                button("Honeycrisp")
+            ]),
+            submenu("Citrus", vec![
+                button("Lemon"),
+                button("Orange")
+            ]),
+            button("Banana")
+        ]),
+        submenu("Vegetables", vec![
+            button("Carrot"),
+            button("Potato")
+        ]),
+        back_button("Exit")
+    ]);
+
+    // right opens the selected submenu, left goes back
+    mut_menu(&menu).set_miller_columns(true);
+    mut_menu(&menu).set_help_bar(true);
+
+    run(&menu);
+    println!("{:?}", mut_menu(&menu).get_latest_menu_name());
+}
@@ -0,0 +1,22 @@
+//!
+//! Click, double click and scroll with the mouse. (run this example yourself)
+//!
+
+fn main() {
+    use terminal_menu::{menu, label, button, list, scroll, run, mut_menu};
+    let mut items = vec![
+        label("click to select, double click to press"),
+        list("Size", vec!["small", "medium", "large"]),
+        scroll("Color", vec!["red", "green", "blue"]),
+    ];
+    items.extend((1..50).map(|n| button(format!("{}", n))));
+    let menu = menu(items);
+
+    // the mouse is only used in full screen
+    mut_menu(&menu).set_mouse(true);
+    mut_menu(&menu).set_scrollbar(true);
+
+    run(&menu);
+    let menu = mut_menu(&menu);
+    println!("{} ({}, {})", menu.selected_item_name(), menu.selection_value("Size"), menu.selection_value("Color"));
+}
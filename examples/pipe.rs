@@ -0,0 +1,25 @@
+//!
+//! Draw the menu on the terminal so that stdout can be piped.
+//! try: cargo run --example pipe | cat
+//!
+
+fn main() {
+    use terminal_menu::{menu, button, run, mut_menu, tty};
+    let menu = menu(vec![
+        button("Alice"),
+        button("Bob"),
+        button("Charlie")
+    ]);
+
+    // tty:
+    //  the controlling terminal of the program
+    //  fall back to stderr if there is none
+    match tty() {
+        Ok(tty) => mut_menu(&menu).set_backend(tty),
+        Err(_) => mut_menu(&menu).set_backend(std::io::stderr())
+    }
+    run(&menu);
+
+    // only this ends up in the pipe
+    println!("{}", mut_menu(&menu).selected_item_name());
+}
@@ -0,0 +1,23 @@
+//!
+//! Preview the selected item next to the items, like fzf.
+//!
+
+fn main() {
+    use terminal_menu::{menu, button, run, mut_menu, PreviewPosition};
+
+    // pick one of the examples
+    let mut names: Vec<String> = std::fs::read_dir("examples").unwrap()
+        .map(|a| a.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    let menu = menu(names.iter().map(button).collect());
+
+    // the preview is made on another thread, the menu can be used while it is slow
+    mut_menu(&menu).set_preview(PreviewPosition::Right, |name| {
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        std::fs::read_to_string(format!("examples/{}", name)).unwrap_or_else(|e| e.to_string())
+    });
+
+    run(&menu);
+    println!("{}", mut_menu(&menu).selected_item_name());
+}
@@ -0,0 +1,34 @@
+//!
+//! Header, footer and a status line that is updated while the menu is open.
+//!
+
+fn main() {
+    use terminal_menu::{menu, button, activate, wait_for_exit, has_exited, mut_menu, Status};
+    let menu = menu(
+
+        // create buttons representing numbers from 1 to 100
+        (1..100).map(|n| button(format!("{}", n))).collect()
+
+    );
+
+    // header and footer stay in place when the list scrolls
+    mut_menu(&menu).set_header(vec!["Pick a number", "-------------"]);
+    mut_menu(&menu).set_footer(vec!["-------------"]);
+
+    let status = Status::new("counting...");
+    mut_menu(&menu).set_status(status.clone());
+
+    activate(&menu);
+
+    // update the status line from this thread while the menu is open
+    for seconds in 1.. {
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        if has_exited(&menu) {
+            break;
+        }
+        status.set(format!("open for {} seconds", seconds));
+    }
+
+    wait_for_exit(&menu);
+    println!("{}", mut_menu(&menu).selected_item_name());
+}
@@ -29,6 +29,10 @@ fn main() {
 
         button("exit")
     ]);
+
+    // show which keys work on the selected item, submenus show it too
+    mut_menu(&menu).set_help_bar(true);
+
     run(&menu);
 
     // name of the menu active before exiting
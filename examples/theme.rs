@@ -0,0 +1,28 @@
+//!
+//! Change the glyphs and styles of a menu with a theme.
+//!
+
+fn main() {
+    use terminal_menu::{menu, label, button, list, submenu, back_button, run, mut_menu, Theme};
+    use crossterm::style::{Color, ContentStyle, Stylize};
+    let menu = menu(vec![
+        label("themed menu"),
+        list("Fruit", vec!["Apple", "Banana", "Cherry"]),
+        submenu("More", vec![
+            label("submenus use the theme of their parent"),
+            back_button("Back")
+        ]),
+        button("Done")
+    ]);
+
+    // theme:
+    //  every field has a default, override only what you need
+    mut_menu(&menu).set_theme(Theme {
+        cursor: "▶ ".to_owned(),
+        selected: ContentStyle::new().with(Color::Black).on(Color::Yellow),
+        value: ContentStyle::new().with(Color::Blue),
+        label_text: ContentStyle::new().dim(),
+        ..Theme::default()
+    });
+    run(&menu);
+}
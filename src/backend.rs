@@ -0,0 +1,128 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, IsTerminal, Stderr, Stdout, Write};
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+use crossterm::terminal;
+
+/// Something a terminal-menu can be drawn on.
+/// Implemented for stdout, stderr, files (such as "/dev/tty"), tcp streams and Buffer.
+/// # Example
+/// ```
+/// use terminal_menu::{menu, button, mut_menu};
+/// let my_menu = menu(vec![
+///     button("Alice"),
+///     button("Bob")
+/// ]);
+/// mut_menu(&my_menu).set_backend(std::io::stderr());
+/// ```
+pub trait Backend: Write + Send {
+
+    /// Size of the drawing area as (columns, rows).
+    /// Defaults to the size of the terminal, or 80x24 if it can't be queried.
+    fn size(&self) -> (u16, u16) {
+        terminal::size().unwrap_or((80, 24))
+    }
+
+}
+
+impl Backend for Stdout {}
+impl Backend for Stderr {}
+impl Backend for File {}
+impl Backend for TcpStream {}
+impl<W: Backend> Backend for BufWriter<W> {
+    fn size(&self) -> (u16, u16) {
+        self.get_ref().size()
+    }
+}
+
+/// An in-memory backend of a fixed size.
+/// Clones share the contents, so a clone kept by the caller reads what the menu drew.
+/// # Example
+/// ```
+/// use terminal_menu::{menu, button, mut_menu, Buffer};
+/// let my_menu = menu(vec![
+///     button("Alice"),
+///     button("Bob")
+/// ]);
+/// let buffer = Buffer::new(40, 10);
+/// mut_menu(&my_menu).set_backend(buffer.clone());
+/// assert!(buffer.contents().is_empty());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Buffer {
+    contents: Arc<Mutex<Vec<u8>>>,
+    size: (u16, u16),
+}
+
+impl Buffer {
+
+    /// An empty buffer of the size, as columns and rows.
+    pub fn new(columns: u16, rows: u16) -> Self {
+        Buffer {
+            contents: Arc::new(Mutex::new(Vec::new())),
+            size: (columns, rows),
+        }
+    }
+
+    /// Everything written to the buffer so far.
+    pub fn contents(&self) -> Vec<u8> {
+        self.contents.lock().unwrap().clone()
+    }
+
+}
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.contents.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Backend for Buffer {
+    fn size(&self) -> (u16, u16) {
+        self.size
+    }
+}
+
+#[cfg(windows)]
+const TTY_OUT: &str = "CONOUT$";
+#[cfg(windows)]
+const TTY_IN: &str = "CONIN$";
+#[cfg(not(windows))]
+const TTY_OUT: &str = "/dev/tty";
+#[cfg(not(windows))]
+const TTY_IN: &str = "/dev/tty";
+
+/// Open the controlling terminal as a backend.
+/// Drawing the menu on it keeps stdout clean, so the output of the program can be piped.
+/// # Example
+/// ```
+/// use terminal_menu::{menu, button, mut_menu, tty};
+/// let my_menu = menu(vec![
+///     button("Alice"),
+///     button("Bob")
+/// ]);
+/// match tty() {
+///     Ok(tty) => mut_menu(&my_menu).set_backend(tty),
+///     Err(_) => mut_menu(&my_menu).set_backend(std::io::stderr()),
+/// }
+/// ```
+pub fn tty() -> io::Result<BufWriter<File>> {
+    let file = OpenOptions::new().write(true).open(TTY_OUT)?;
+    Ok(BufWriter::new(file))
+}
+
+/// Read a line from stdin, or from the controlling terminal when stdin is not one.
+pub(crate) fn read_line(input: &mut String) -> io::Result<usize> {
+    if io::stdin().is_terminal() {
+        io::stdin().read_line(input)
+    } else {
+        BufReader::new(File::open(TTY_IN)?).read_line(input)
+    }
+}
+
+/// A backend shared between a menu and its submenus.
+pub(crate) type SharedBackend = Arc<Mutex<Box<dyn Backend>>>;
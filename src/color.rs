@@ -0,0 +1,197 @@
+use std::env;
+use crossterm::style::{Attribute, Color, ContentStyle};
+
+/// The colors a terminal can show.
+/// Colors the terminal can't show are drawn with the nearest color it can.
+/// # Example
+/// ```
+/// use terminal_menu::{menu, button, mut_menu, ColorSupport};
+/// let my_menu = menu(vec![
+///     button("Alice"),
+///     button("Bob")
+/// ]);
+/// // no colors even if the terminal supports them
+/// mut_menu(&my_menu).set_color_support(ColorSupport::None);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorSupport {
+    /// No colors. Attributes such as bold are still shown and the selected item is reversed.
+    None,
+    /// The 16 basic colors.
+    Ansi16,
+    /// The 256 colors of xterm.
+    Ansi256,
+    /// Any rgb color.
+    TrueColor,
+}
+
+/// The 16 basic colors in the order of their ansi values.
+const BASIC: [Color; 16] = [
+    Color::Black, Color::DarkRed, Color::DarkGreen, Color::DarkYellow,
+    Color::DarkBlue, Color::DarkMagenta, Color::DarkCyan, Color::Grey,
+    Color::DarkGrey, Color::Red, Color::Green, Color::Yellow,
+    Color::Blue, Color::Magenta, Color::Cyan, Color::White,
+];
+
+/// The rgb values xterm uses for the 16 basic colors.
+const BASIC_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0),
+    (0, 0, 238), (205, 0, 205), (0, 205, 205), (229, 229, 229),
+    (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0),
+    (92, 92, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255),
+];
+
+/// The levels of each channel in the 6x6x6 color cube of the 256 colors.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorSupport {
+
+    /// Guess the colors of the terminal from the environment.
+    /// NO_COLOR and TERM=dumb turn colors off, COLORTERM and TERM tell how many there are.
+    pub fn detect() -> Self {
+        if env::var_os("NO_COLOR").is_some_and(|a| !a.is_empty()) {
+            return ColorSupport::None;
+        }
+        let term = env::var("TERM").unwrap_or_default();
+        if term == "dumb" {
+            return ColorSupport::None;
+        }
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" || term.ends_with("-direct") {
+            ColorSupport::TrueColor
+        } else if term.contains("256color") {
+            ColorSupport::Ansi256
+        } else if term.is_empty() && cfg!(windows) {
+            // the windows console supports rgb colors since windows 10
+            ColorSupport::TrueColor
+        } else {
+            ColorSupport::Ansi16
+        }
+    }
+
+    /// The style with its colors replaced by the nearest ones the terminal can show.
+    pub(crate) fn adapt(self, style: ContentStyle) -> ContentStyle {
+        if self == ColorSupport::None {
+            return ContentStyle {
+                foreground_color: None,
+                background_color: None,
+                underline_color: None,
+                attributes: style.attributes,
+            };
+        }
+        ContentStyle {
+            foreground_color: style.foreground_color.map(|a| self.nearest(a)),
+            background_color: style.background_color.map(|a| self.nearest(a)),
+            underline_color: style.underline_color.map(|a| self.nearest(a)),
+            attributes: style.attributes,
+        }
+    }
+
+    /// The style of the selected item, reversed when it relies on colors that can't be shown.
+    pub(crate) fn selected(self, style: ContentStyle) -> ContentStyle {
+        let colored = style.foreground_color.is_some() || style.background_color.is_some();
+        if self == ColorSupport::None && colored && !style.attributes.has(Attribute::Reverse) {
+            let mut style = style;
+            style.attributes.set(Attribute::Reverse);
+            style
+        } else {
+            style
+        }
+    }
+
+    fn nearest(self, color: Color) -> Color {
+        match (self, color) {
+            (ColorSupport::TrueColor, color) => color,
+            (ColorSupport::Ansi256, Color::Rgb { r, g, b }) => Color::AnsiValue(ansi256(r, g, b)),
+            (ColorSupport::Ansi16, Color::AnsiValue(value)) if value < 16 => BASIC[value as usize],
+            (ColorSupport::Ansi16, Color::AnsiValue(value)) => basic(rgb(value)),
+            (ColorSupport::Ansi16, Color::Rgb { r, g, b }) => basic((r, g, b)),
+            (_, color) => color
+        }
+    }
+
+}
+
+/// The rgb value of one of the 256 colors.
+fn rgb(value: u8) -> (u8, u8, u8) {
+    match value {
+        0..=15 => BASIC_RGB[value as usize],
+        16..=231 => {
+            let value = value - 16;
+            (
+                CUBE_LEVELS[(value / 36) as usize],
+                CUBE_LEVELS[(value / 6 % 6) as usize],
+                CUBE_LEVELS[(value % 6) as usize],
+            )
+        }
+        _ => {
+            let gray = 8 + (value - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// The nearest of the 256 colors from the color cube and the grayscale ramp.
+fn ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |x: u8| {
+        (0..6).min_by_key(|i| (CUBE_LEVELS[*i] as i32 - x as i32).abs()).unwrap() as u8
+    };
+    let cube = 16 + 36 * level(r) + 6 * level(g) + level(b);
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray = 232 + ((average.saturating_sub(3) / 10).min(23)) as u8;
+    if distance(rgb(gray), (r, g, b)) < distance(rgb(cube), (r, g, b)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+/// The nearest of the 16 basic colors.
+fn basic(color: (u8, u8, u8)) -> Color {
+    let i = (0..16).min_by_key(|i| distance(BASIC_RGB[*i], color)).unwrap();
+    BASIC[i]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ansi256, basic, ColorSupport};
+    use crossterm::style::Color;
+
+    #[test]
+    fn ansi256_picks_cube_colors() {
+        assert_eq!(ansi256(0, 0, 0), 16);
+        assert_eq!(ansi256(255, 0, 0), 196);
+        assert_eq!(ansi256(255, 255, 255), 231);
+        assert_eq!(ansi256(100, 140, 170), 67);
+    }
+
+    #[test]
+    fn ansi256_picks_grays_between_cube_levels() {
+        assert_eq!(ansi256(128, 128, 128), 244);
+        assert_eq!(ansi256(8, 8, 8), 232);
+        assert_eq!(ansi256(238, 238, 238), 255);
+    }
+
+    #[test]
+    fn basic_picks_the_nearest_of_16() {
+        assert_eq!(basic((250, 10, 10)), Color::Red);
+        assert_eq!(basic((120, 120, 120)), Color::DarkGrey);
+        assert_eq!(basic((0, 0, 0)), Color::Black);
+    }
+
+    #[test]
+    fn nearest_keeps_colors_the_terminal_can_show() {
+        let red = Color::Rgb { r: 255, g: 0, b: 0 };
+        assert_eq!(ColorSupport::TrueColor.nearest(red), red);
+        assert_eq!(ColorSupport::Ansi256.nearest(red), Color::AnsiValue(196));
+        assert_eq!(ColorSupport::Ansi256.nearest(Color::Blue), Color::Blue);
+        assert_eq!(ColorSupport::Ansi16.nearest(Color::AnsiValue(3)), Color::DarkYellow);
+        assert_eq!(ColorSupport::Ansi16.nearest(Color::AnsiValue(196)), Color::Red);
+        assert_eq!(ColorSupport::Ansi16.nearest(red), Color::Red);
+    }
+}
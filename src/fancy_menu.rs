@@ -1,161 +1,1191 @@
-use std::io::{stdout, Write, stdin};
-use crate::{TerminalMenu, TerminalMenuStruct, TMIKind, utils, back_button, PrintState};
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::io::{stdout, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::{TerminalMenu, TerminalMenuItem, TerminalMenuStruct, TMIKind, View, Column, Hit, utils, PrintState, PrintMode, Overflow, Border, PreviewPosition, ColorSupport, ValueAlignment};
+use crate::utils::Span;
+use crate::theme::{Theme, merge};
+use crate::backend::{self, SharedBackend};
+use crate::preview::Preview;
+use crate::keymap::{Action, HotkeyError, KeyMap};
+use crate::fuzzy::{fuzzy_match, fuzzy_rank};
 use crossterm::*;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use lazy_static::lazy_static;
 
 pub fn run(menu: TerminalMenu) {
-    {
+    let backend = {
         let mut menu_wr = menu.write().unwrap();
+        debug_assert_eq!(menu_wr.check_hotkeys(), Ok(()), "hotkeys of the menu conflict");
         menu_wr.active = true;
         menu_wr.exited = false;
         menu_wr.canceled = false;
+        menu_wr.query = None;
 
-        menu_wr.longest_name = menu_wr.items.iter().map(|a| a.name.len()).max().unwrap();
+        menu_wr.longest_name = menu_wr.items.iter().map(name_width).max().unwrap();
 
         print(&mut menu_wr);
-    }
+        backend(&menu_wr)
+    };
 
-    terminal::enable_raw_mode().unwrap();
-    execute!(
-        stdout(),
-        cursor::Hide
-    ).unwrap();
+    let mouse = mouse(&menu.read().unwrap());
+    enter_raw_mode(&backend, mouse);
 
     while menu.read().unwrap().active {
         handle_input(&menu);
+        let mut menu_wr = menu.write().unwrap();
+        if menu_wr.active && outdated(&menu_wr) {
+            print(&mut menu_wr);
+        }
     }
 
     terminal::disable_raw_mode().unwrap();
-    execute!(
-        stdout(),
-        cursor::Show,
-    ).unwrap();
 
     {
         let mut menu_wr = menu.write().unwrap();
+        if !shares_screen(&menu_wr) {
+            erase(&mut menu_wr);
+        }
+        let mut out = backend.lock().unwrap();
+        if let PrintMode::Inline { summary: true } = print_mode(&menu_wr) {
+            if !menu_wr.canceled {
+                queue!(
+                    out,
+                    style::Print(summary(&menu_wr)),
+                    style::Print("\r\n")
+                ).unwrap();
+            }
+        }
+        if mouse {
+            queue!(out, event::DisableMouseCapture).unwrap();
+        }
         execute!(
-            stdout(),
-            terminal::LeaveAlternateScreen
+            out,
+            cursor::Show
         ).unwrap();
-        menu_wr.printed = PrintState::None;
         menu_wr.exited = true;
     }
 }
 
-fn print(menu_wr: &mut TerminalMenuStruct) {
-    print_big(menu_wr);
+/// Check the hotkeys of the menu and its submenus against the keymap they use and each other.
+/// Labels are left out, their hotkeys do nothing.
+pub(crate) fn check_hotkeys(menu: &TerminalMenuStruct, inherited: Option<&KeyMap>) -> std::result::Result<(), HotkeyError> {
+    let keymap = menu.keymap.as_ref().or(inherited).cloned().unwrap_or_default();
+    for (i, item) in menu.items.iter().enumerate() {
+        if let (Some(key), false) = (item.hotkey, matches!(item.kind, TMIKind::Label)) {
+            let upper = key.to_uppercase().next().unwrap_or(key);
+            for c in [key, upper] {
+                if let Some(action) = keymap.action(&KeyCode::Char(c).into()) {
+                    return Err(HotkeyError::Bound { item: item.name.clone(), key, action });
+                }
+            }
+            if let Some(other) = menu.items[..i].iter().find(|a| a.hotkey == Some(key) && !matches!(a.kind, TMIKind::Label)) {
+                return Err(HotkeyError::Duplicate { items: (other.name.clone(), item.name.clone()), key });
+            }
+        }
+        if let TMIKind::Submenu(submenu) = &item.kind {
+            check_hotkeys(&submenu.read().unwrap(), Some(&keymap))?;
+        }
+    }
+    Ok(())
 }
 
-fn print_big(menu: &mut TerminalMenuStruct) {
-    let term_height = utils::term_height();
-    if term_height <= 3 {
-        return;
+/// The backend of the menu, defaulting to stdout.
+fn backend(menu: &TerminalMenuStruct) -> SharedBackend {
+    match &menu.backend {
+        Some(backend) => backend.clone(),
+        None => Arc::new(Mutex::new(Box::new(stdout())))
+    }
+}
+
+/// Returns true if the status line was changed or the preview became ready since the menu was drawn.
+fn outdated(menu: &TerminalMenuStruct) -> bool {
+    let status_changed = match &menu.status {
+        Some(status) => status.get() != menu.view.status,
+        None => false
+    };
+    let preview_ready = match &menu.preview {
+        Some((_, preview)) => {
+            menu.view.preview != Some(menu.selected) && preview.get(menu.selected).is_some()
+        }
+        None => false
+    };
+    status_changed || preview_ready
+}
+
+fn print_mode(menu: &TerminalMenuStruct) -> PrintMode {
+    menu.print_mode.unwrap_or(PrintMode::FullScreen)
+}
+
+fn theme(menu: &TerminalMenuStruct) -> Cow<'_, Theme> {
+    let mut theme = match &menu.theme {
+        Some(theme) => Cow::Borrowed(theme),
+        None => Cow::Owned(Theme::default())
+    };
+    let selected = color_support(menu).selected(theme.selected);
+    if selected != theme.selected {
+        theme.to_mut().selected = selected;
+    }
+    theme
+}
+
+lazy_static! {
+    static ref DETECTED_COLORS: ColorSupport = ColorSupport::detect();
+    static ref DOUBLE_CLICK: Duration = Duration::from_millis(500);
+}
+
+fn color_support(menu: &TerminalMenuStruct) -> ColorSupport {
+    menu.color_support.unwrap_or(*DETECTED_COLORS)
+}
+
+fn border(menu: &TerminalMenuStruct) -> Border {
+    menu.border.unwrap_or(Border::None)
+}
+
+fn scrolloff(menu: &TerminalMenuStruct) -> usize {
+    menu.scrolloff.unwrap_or(3)
+}
+
+fn keymap(menu: &TerminalMenuStruct) -> Cow<'_, KeyMap> {
+    match &menu.keymap {
+        Some(keymap) => Cow::Borrowed(keymap),
+        None => Cow::Owned(KeyMap::default())
+    }
+}
+
+/// The mouse is used only in full screen, where the rows of the screen are known.
+fn mouse(menu: &TerminalMenuStruct) -> bool {
+    menu.mouse.unwrap_or(false) && print_mode(menu) == PrintMode::FullScreen
+}
+
+fn filter(menu: &TerminalMenuStruct) -> bool {
+    menu.filter.unwrap_or(false)
+}
+
+fn miller_columns(menu: &TerminalMenuStruct) -> bool {
+    menu.miller_columns.unwrap_or(false)
+}
+
+/// A submenu in miller columns takes over the full screen of its parent instead of drawing a screen of its own.
+fn shares_screen(menu: &TerminalMenuStruct) -> bool {
+    menu.view.parent_column.is_some() && print_mode(menu) == PrintMode::FullScreen
+}
+
+fn overflow(menu: &TerminalMenuStruct) -> Overflow {
+    menu.overflow.unwrap_or(Overflow::Truncate)
+}
+
+/// Make the child menu draw like its parent unless it has settings of its own.
+fn inherit(parent: &TerminalMenuStruct, child: &TerminalMenu) {
+    let mut child = child.write().unwrap();
+    if child.backend.is_none() {
+        child.backend = parent.backend.clone();
+    }
+    if child.print_mode.is_none() {
+        // only the outermost menu leaves a summary
+        child.print_mode = parent.print_mode.map(|mode| match mode {
+            PrintMode::Inline { .. } => PrintMode::Inline { summary: false },
+            mode => mode
+        });
+    }
+    if child.overflow.is_none() {
+        child.overflow = parent.overflow;
+    }
+    if child.theme.is_none() {
+        child.theme = parent.theme.clone();
+    }
+    if child.color_support.is_none() {
+        child.color_support = parent.color_support;
+    }
+    if child.centered.is_none() {
+        child.centered = parent.centered;
+    }
+    if child.max_width.is_none() {
+        child.max_width = parent.max_width;
+    }
+    if child.value_alignment.is_none() {
+        child.value_alignment = parent.value_alignment;
+    }
+    if child.keymap.is_none() {
+        child.keymap = parent.keymap.clone();
+    }
+    if child.filter.is_none() {
+        child.filter = parent.filter;
+    }
+    if child.mouse.is_none() {
+        child.mouse = parent.mouse;
+    }
+    if child.miller_columns.is_none() {
+        child.miller_columns = parent.miller_columns;
+    }
+    if child.border.is_none() {
+        child.border = parent.border;
+    }
+    if child.padding.is_none() {
+        child.padding = parent.padding;
+    }
+    if child.margin.is_none() {
+        child.margin = parent.margin;
+    }
+    if child.status.is_none() {
+        child.status = parent.status.clone();
+    }
+    if child.help_bar.is_none() {
+        child.help_bar = parent.help_bar;
+    }
+    if child.scrolloff.is_none() {
+        child.scrolloff = parent.scrolloff;
+    }
+    if child.scrollbar.is_none() {
+        child.scrollbar = parent.scrollbar;
     }
-    if let PrintState::None = menu.printed {
+    if child.position_counter.is_none() {
+        child.position_counter = parent.position_counter;
+    }
+}
+
+fn enter_raw_mode(backend: &SharedBackend, mouse: bool) {
+    terminal::enable_raw_mode().unwrap();
+    let mut out = backend.lock().unwrap();
+    if mouse {
+        queue!(out, event::EnableMouseCapture).unwrap();
+    }
+    execute!(
+        out,
+        cursor::Hide
+    ).unwrap();
+}
+
+/// Read a line of input below the menu.
+fn read_line(backend: &SharedBackend, printed: &mut PrintState, mouse: bool, prompt: &str) -> String {
+    let mut out = backend.lock().unwrap();
+    if mouse {
+        // clicks would be read as text
+        queue!(out, event::DisableMouseCapture).unwrap();
+    }
+    if let PrintState::Big(_) = printed {
         queue!(
-            stdout(),
-            terminal::EnterAlternateScreen
+            out,
+            cursor::MoveToNextLine(100)
         ).unwrap();
     }
-    queue!(
-        stdout(),
-        cursor::MoveTo(0, 0),
-        terminal::Clear(terminal::ClearType::All),
-        style::Print("..."),
+    execute!(
+        out,
+        style::Print(prompt),
+        cursor::Show,
     ).unwrap();
-    println!("\r");
+    terminal::disable_raw_mode().unwrap();
+    let mut input = String::new();
+    backend::read_line(&mut input).unwrap();
+    terminal::enable_raw_mode().unwrap();
+    if mouse {
+        queue!(out, event::EnableMouseCapture).unwrap();
+    }
+    execute!(
+        out,
+        cursor::Hide,
+    ).unwrap();
+    utils::unprint(&mut *out, 1);
+    out.flush().unwrap();
+    if let PrintState::Big(frame) = printed {
+        // enter scrolled the screen up a line, draw everything again
+        frame.clear();
+    }
+    input.trim().to_owned()
+}
+
+/// Remove the menu from the terminal.
+fn erase(menu: &mut TerminalMenuStruct) {
+    let backend = backend(menu);
+    let mut out = backend.lock().unwrap();
+    match &menu.printed {
+        PrintState::None => {}
+        PrintState::Big(_) => {
+            queue!(
+                out,
+                terminal::LeaveAlternateScreen
+            ).unwrap();
+        }
+        PrintState::Small(frame) => utils::unprint(&mut *out, frame.len()),
+    }
+    out.flush().unwrap();
+    menu.printed = PrintState::None;
+}
 
-    let item_count = menu.items.len().min(term_height - 3);
-    let mut top = 0;
-    if menu.selected > item_count / 2 {
-        top = menu.selected - item_count / 2;
-        if top + item_count > menu.items.len() {
-            top = menu.items.len() - item_count;
+/// The selected item and its value on a single line.
+/// Follows the submenu the menu was exited from.
+fn summary(menu: &TerminalMenuStruct) -> String {
+    let item = &menu.items[menu.selected];
+    match &item.kind {
+        TMIKind::Submenu(submenu) if submenu.read().unwrap().exit.is_some() => {
+            summary(&submenu.read().unwrap())
         }
+        TMIKind::Scroll { values, selected } |
+        TMIKind::List   { values, selected } => format!("{}: {}", item.name, values[*selected]),
+        TMIKind::String { value, .. } => format!("{}: {}", item.name, value),
+        TMIKind::Numeric { value, .. } => format!("{}: {}", item.name, value),
+        _ => item.name.clone()
     }
-    for i in top..(top + item_count) {
-        print_item(menu, i);
-        println!("\r");
+}
+
+/// The range of rows visible when at most count of the len rows fit on the screen.
+/// Scrolls from the top row only as much as needed to keep the scrolloff around the selected row.
+fn scroll(top: usize, selected: usize, len: usize, count: usize, scrolloff: usize) -> std::ops::Range<usize> {
+    let count = len.min(count);
+    let scrolloff = scrolloff.min(count.saturating_sub(1) / 2);
+    let mut top = top.min(len - count);
+    if selected < top + scrolloff {
+        top = selected.saturating_sub(scrolloff);
     }
-    println!("...");
-    menu.printed = PrintState::Big;
+    if selected + scrolloff >= top + count {
+        top = (selected + scrolloff + 1 - count).min(len - count);
+    }
+    top..(top + count)
 }
 
-fn print_item(menu: &TerminalMenuStruct, index: usize) {
-    if menu.selected == index {
+fn print(menu_wr: &mut TerminalMenuStruct) {
+    match print_mode(menu_wr) {
+        PrintMode::FullScreen => print_big(menu_wr),
+        PrintMode::Inline { .. } => print_small(menu_wr),
+    }
+}
+
+fn print_big(menu: &mut TerminalMenuStruct) {
+    let backend = backend(menu);
+    let mut out = backend.lock().unwrap();
+    let (term_width, term_height) = out.size();
+    let (term_width, term_height) = (term_width as usize, term_height as usize);
+    if term_height <= 3 || term_width <= 1 {
+        return;
+    }
+    let mut view = std::mem::take(&mut menu.view);
+    let frame = layout(menu, &mut view, term_width - 1, term_height - 1);
+    menu.view = view;
+
+    let old = match std::mem::replace(&mut menu.printed, PrintState::None) {
+        PrintState::Big(old) => old,
+        _ => {
+            queue!(
+                out,
+                terminal::EnterAlternateScreen
+            ).unwrap();
+            Vec::new()
+        }
+    };
+    if old.is_empty() {
         queue!(
-            stdout(),
-            crossterm::style::SetForegroundColor(crossterm::style::Color::Cyan),
-            crossterm::style::Print("> "),
-            crossterm::style::Print(&menu.items[index].name),
+            out,
+            terminal::Clear(terminal::ClearType::All)
         ).unwrap();
-    } else {
+    }
+    for (row, line) in frame.iter().enumerate() {
+        if old.get(row) != Some(line) {
+            queue!(
+                out,
+                cursor::MoveTo(0, row as u16),
+                style::Print(line),
+                terminal::Clear(terminal::ClearType::UntilNewLine)
+            ).unwrap();
+        }
+    }
+    for row in frame.len()..old.len() {
         queue!(
-            stdout(),
-            crossterm::style::Print("  "),
-            crossterm::style::SetForegroundColor(menu.items[index].color),
-            crossterm::style::Print(&menu.items[index].name)
+            out,
+            cursor::MoveTo(0, row as u16),
+            terminal::Clear(terminal::ClearType::CurrentLine)
         ).unwrap();
     }
+    out.flush().unwrap();
+    menu.printed = PrintState::Big(frame);
+}
+
+fn print_small(menu: &mut TerminalMenuStruct) {
+    let backend = backend(menu);
+    let mut out = backend.lock().unwrap();
+    let (term_width, term_height) = out.size();
+    let (term_width, term_height) = (term_width as usize, term_height as usize);
+    if term_height <= 1 || term_width <= 1 {
+        return;
+    }
+    let mut view = std::mem::take(&mut menu.view);
+    let frame = layout(menu, &mut view, term_width - 1, term_height - 1);
+    menu.view = view;
 
-    for _ in menu.items[index].name.len()..menu.longest_name + 5 {
+    let old = match std::mem::replace(&mut menu.printed, PrintState::None) {
+        PrintState::Small(old) => old,
+        _ => Vec::new()
+    };
+    if old.len() == frame.len() && !old.is_empty() {
+        // same shape, rewrite changed rows in place
         queue!(
-            stdout(),
-            crossterm::style::Print(" ")
+            out,
+            cursor::MoveToPreviousLine(old.len() as u16)
         ).unwrap();
+        for (old_line, line) in old.iter().zip(&frame) {
+            if old_line != line {
+                queue!(
+                    out,
+                    style::Print(line),
+                    terminal::Clear(terminal::ClearType::UntilNewLine)
+                ).unwrap();
+            }
+            queue!(out, style::Print("\r\n")).unwrap();
+        }
+    } else {
+        utils::unprint(&mut *out, old.len());
+        for line in &frame {
+            queue!(out, style::Print(line), style::Print("\r\n")).unwrap();
+        }
+    }
+    out.flush().unwrap();
+    menu.printed = PrintState::Small(frame);
+}
+
+/// Lay out the whole menu in the given size, one string per row.
+fn layout(menu: &TerminalMenuStruct, view: &mut View, width: usize, height: usize) -> Vec<String> {
+    view.hits.clear();
+    let theme = theme(menu);
+    let border = border(menu).glyphs();
+    let padding = menu.padding.unwrap_or(0);
+    let margin = menu.margin.unwrap_or(0);
+    let edge = margin + padding + border.is_some() as usize;
+    if width <= edge * 2 || height <= edge * 2 {
+        return Vec::new();
+    }
+    let mut inner_width = (width - edge * 2).min(menu.max_width.unwrap_or(usize::MAX));
+    let inner_height = height - edge * 2;
+    let (center_horizontally, center_vertically) = menu.centered.unwrap_or((false, false));
+
+    let mut header: Vec<Vec<Span>> = menu.header.iter()
+        .flat_map(|a| text_rows(menu, a, theme.header, inner_width))
+        .collect();
+    if let Some(query) = &menu.query {
+        let shown = shown_items(menu).len();
+        let total = menu.items.iter().filter(|a| !matches!(a.kind, TMIKind::Label)).count();
+        header.extend(text_rows(menu, &format!("/{}  ({}/{})", query, shown, total), theme.header, inner_width));
+    }
+    let mut footer: Vec<Vec<Span>> = menu.footer.iter()
+        .flat_map(|a| text_rows(menu, a, theme.header, inner_width))
+        .collect();
+    view.status = menu.status.as_ref().map(|a| a.get()).unwrap_or_default();
+    if !view.status.is_empty() {
+        footer.extend(text_rows(menu, &view.status, theme.header, inner_width));
+    }
+    if menu.items.iter().any(|a| !a.description.is_empty()) {
+        footer.splice(0..0, description_pane(menu, &theme, inner_width, inner_height / 3));
+    }
+    if menu.help_bar.unwrap_or(false) {
+        footer.extend(text_rows(menu, &help(menu, view), theme.header, inner_width));
+    }
+    let items_height = inner_height.saturating_sub(header.len() + footer.len());
+    let items_start = padding + header.len();
+
+    let mut rows = vec![Vec::new(); padding];
+    rows.extend(header);
+    rows.extend(body(menu, view, &theme, inner_width, items_height));
+    rows.extend(footer);
+    rows.extend(vec![Vec::new(); padding]);
+    shift(&mut view.hits, items_start, 0);
+    if menu.popup.is_some() {
+        let anchor = (items_start + view.anchor.0, view.anchor.1);
+        popup(menu, view, &theme, &mut rows, anchor, inner_width, padding + inner_height);
+    }
+
+    let colors = color_support(menu);
+    if center_horizontally {
+        // shrink to the content so there is something to center
+        let title_width = match (&border, &menu.name) {
+            (Some(_), Some(name)) => (utils::width(name) + 4).saturating_sub(padding * 2),
+            _ => 0
+        };
+        let content_width = rows.iter().map(|a| utils::spans_width(a)).max().unwrap_or(0);
+        inner_width = inner_width.min(content_width.max(title_width));
+    }
+    let offset = if center_horizontally { (width - inner_width - edge * 2) / 2 } else { 0 };
+
+    shift(&mut view.hits, margin + border.is_some() as usize, margin + offset + border.is_some() as usize + padding);
+
+    let mut frame = vec![String::new(); margin];
+    let indent = " ".repeat(margin + offset);
+    let no_style = style::ContentStyle::new();
+    let title = menu.name.as_deref().unwrap_or("");
+    if let Some(glyphs) = &border {
+        let mut top = vec![utils::span(format!("{}{}", indent, glyphs.top_left), theme.border)];
+        let fill = inner_width + padding * 2;
+        if title.is_empty() || fill < 4 {
+            top.push(utils::span(glyphs.horizontal.to_string().repeat(fill), theme.border));
+        } else {
+            let title = utils::truncate(vec![utils::span(title, theme.header)], fill - 4);
+            let title_width = utils::spans_width(&title);
+            top.push(utils::span(format!("{} ", glyphs.horizontal), theme.border));
+            top.extend(title);
+            top.push(utils::span(format!(" {}", glyphs.horizontal.to_string().repeat(fill - 3 - title_width)), theme.border));
+        }
+        top.push(utils::span(glyphs.top_right.to_string(), theme.border));
+        frame.push(utils::render(&top, colors));
+    }
+    for row in rows {
+        let mut line = vec![utils::span(indent.as_str(), no_style)];
+        if let Some(glyphs) = &border {
+            line.push(utils::span(glyphs.vertical.to_string(), theme.border));
+        }
+        line.push(utils::span(" ".repeat(padding), no_style));
+        let row_width = utils::spans_width(&row);
+        line.extend(row);
+        if let Some(glyphs) = &border {
+            line.push(utils::span(" ".repeat(inner_width.saturating_sub(row_width) + padding), no_style));
+            line.push(utils::span(glyphs.vertical.to_string(), theme.border));
+        }
+        frame.push(utils::render(&line, colors));
+    }
+    if let Some(glyphs) = &border {
+        frame.push(utils::render(&[utils::span(format!("{}{}{}{}",
+            indent,
+            glyphs.bottom_left,
+            glyphs.horizontal.to_string().repeat(inner_width + padding * 2),
+            glyphs.bottom_right
+        ), theme.border)], colors));
+    }
+    frame.extend(vec![String::new(); margin]);
+    if center_vertically && print_mode(menu) == PrintMode::FullScreen {
+        let offset = height.saturating_sub(frame.len()) / 2;
+        frame.splice(0..0, vec![String::new(); offset]);
+        shift(&mut view.hits, offset, 0);
+    }
+    frame
+}
+
+/// How a key is shown in the help bar.
+fn key_name(key: &KeyEvent) -> String {
+    let name = match key.code {
+        KeyCode::Up => "↑".to_owned(),
+        KeyCode::Down => "↓".to_owned(),
+        KeyCode::Left => "←".to_owned(),
+        KeyCode::Right => "→".to_owned(),
+        KeyCode::Enter => "Enter".to_owned(),
+        KeyCode::Esc => "Esc".to_owned(),
+        KeyCode::Tab => "Tab".to_owned(),
+        KeyCode::Backspace => "Backspace".to_owned(),
+        KeyCode::Char(' ') => "Space".to_owned(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        code => format!("{:?}", code)
+    };
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        format!("Ctrl+{}", name)
+    } else if key.modifiers.contains(KeyModifiers::ALT) {
+        format!("Alt+{}", name)
+    } else {
+        name
+    }
+}
+
+/// The first key of each of the actions joined with slashes, such as "↑/↓".
+/// Actions without keys are left out.
+fn keys(keymap: &KeyMap, actions: &[Action]) -> String {
+    actions.iter()
+        .filter_map(|action| keymap.keys(*action).first().map(key_name))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The keys that do something with the selected item, and what they do.
+fn help(menu: &TerminalMenuStruct, view: &View) -> String {
+    let keymap = keymap(menu);
+    let hint = |actions: &[Action], text: &str| {
+        let keys = keys(&keymap, actions);
+        if keys.is_empty() { None } else { Some(format!("{} {}", keys, text)) }
+    };
+    if menu.popup.is_some() {
+        let hints = [
+            hint(&[Action::MoveUp, Action::MoveDown], "move"),
+            hint(&[Action::Activate], "pick"),
+            hint(&[Action::Cancel], "close"),
+            Some("type to filter".to_owned()),
+        ];
+        return hints.into_iter().flatten().collect::<Vec<_>>().join("  ");
+    }
+    let mut hints = vec![hint(&[Action::MoveUp, Action::MoveDown], "move")];
+    if changeable(menu) {
+        hints.push(hint(&[Action::Decrement, Action::Increment], "change"));
+    } else if miller_columns(menu) {
+        if view.parent_column.is_some() {
+            hints.push(hint(&[Action::Decrement], "back"));
+        }
+        if let TMIKind::Submenu(_) = menu.items[menu.selected].kind {
+            hints.push(hint(&[Action::Increment], "open"));
+        }
+    } else if menu.grid {
+        hints[0] = hint(&[Action::MoveUp, Action::MoveDown, Action::Decrement, Action::Increment], "move");
+    }
+    let enter = match &menu.items[menu.selected].kind {
+        TMIKind::Button => "select",
+        TMIKind::BackButton => "back",
+        TMIKind::Scroll { .. } | TMIKind::List { .. } => "pick",
+        TMIKind::String { .. } | TMIKind::Numeric { .. } => "edit",
+        TMIKind::Submenu(_) => "open",
+        TMIKind::Label => ""
+    };
+    if !enter.is_empty() {
+        hints.push(hint(&[Action::Activate], enter));
+    }
+    if menu.query.is_some() {
+        hints.push(hint(&[Action::Cancel], "clear"));
+    } else {
+        hints.push(hint(&[Action::Cancel], "cancel"));
+        if filter(menu) {
+            hints.push(hint(&[Action::Filter], "filter"));
+        }
+    }
+    hints.into_iter().flatten().collect::<Vec<_>>().join("  ")
+}
+
+/// The description of the selected item below an empty row.
+/// Always as tall as the longest description, up to the max height, so the items don't move.
+fn description_pane(menu: &TerminalMenuStruct, theme: &Theme, width: usize, max_height: usize) -> Vec<Vec<Span>> {
+    if max_height < 2 {
+        return Vec::new();
+    }
+    let height = menu.items.iter()
+        .map(|a| utils::wrap_words(&a.description, theme.header, width).len())
+        .max()
+        .unwrap_or(0)
+        .min(max_height - 1);
+    let mut rows = utils::wrap_words(&menu.items[menu.selected].description, theme.header, width);
+    if rows.len() > height {
+        rows.truncate(height);
+        let mut last = rows.pop().unwrap();
+        last.push(utils::span("…", theme.header));
+        rows.push(utils::truncate(last, width));
+    }
+    rows.resize(height, Vec::new());
+    rows.insert(0, Vec::new());
+    rows
+}
+
+/// Draw the values of the selected item in a box over the rows, below the anchor or above it if there is more room.
+fn popup(menu: &TerminalMenuStruct, view: &mut View, theme: &Theme, rows: &mut Vec<Vec<Span>>, anchor: (usize, usize), width: usize, height: usize) {
+    let (values, current) = match &menu.items[menu.selected].kind {
+        TMIKind::Scroll { values, selected } |
+        TMIKind::List   { values, selected } => (values, *selected),
+        _ => return
+    };
+    let selected = menu.popup.unwrap_or(current);
+    let shown = popup_values(menu);
+    let glyphs = border(menu).glyphs().or(Border::Single.glyphs()).unwrap();
+    let cursor_width = utils::width(&theme.cursor);
+    let inner_width = (values.iter().map(|a| utils::width(a)).max().unwrap_or(0) + cursor_width).min(width.saturating_sub(2));
+    let below = height.saturating_sub(anchor.0 + 1);
+    let above = anchor.0.min(height);
+    let wanted = shown.len().max(1) + 2;
+    let (start, box_height) = if wanted <= below || below >= above {
+        (anchor.0 + 1, wanted.min(below))
+    } else {
+        (anchor.0 - wanted.min(above), wanted.min(above))
+    };
+    if box_height < 3 || inner_width == 0 {
+        return;
+    }
+    let position = shown.iter().position(|a| *a == selected).unwrap_or(0);
+    let range = scroll(view.popup_top, position, shown.len(), box_height - 2, scrolloff(menu));
+    view.popup_top = range.start;
+
+    let edge = |left: char, right: char, more: bool| {
+        let mut line = glyphs.horizontal.to_string().repeat(inner_width);
+        if more {
+            line.pop();
+            line.push('…');
+        }
+        vec![utils::span(format!("{}{}{}", left, line, right), theme.border)]
+    };
+    let mut top = edge(glyphs.top_left, glyphs.top_right, range.start > 0);
+    if !menu.popup_query.is_empty() {
+        // the filter is written over the top edge
+        let query = utils::truncate(vec![utils::span(format!("/{}", menu.popup_query), theme.header)], inner_width);
+        top = utils::overlay(top, 1, query);
+    }
+    let mut lines = vec![top];
+    for i in shown[range.clone()].iter().copied() {
+        let (cursor, style) = if i == selected {
+            (theme.cursor.clone(), theme.selected)
+        } else if i == current {
+            (" ".repeat(cursor_width), theme.current_value)
+        } else {
+            (" ".repeat(cursor_width), theme.name)
+        };
+        let mut value = vec![utils::span(cursor, style), utils::span(values[i].as_str(), style)];
+        if let Some(found) = fuzzy_match(&menu.popup_query, &values[i]) {
+            value = utils::highlight(value, &found.positions.iter().map(|a| a + cursor_width).collect::<Vec<_>>(), theme.matched);
+        }
+        let value = utils::truncate(value, inner_width);
+        let gap = inner_width - utils::spans_width(&value);
+        let mut line = vec![utils::span(glyphs.vertical.to_string(), theme.border)];
+        line.extend(value);
+        line.push(utils::span(" ".repeat(gap), style::ContentStyle::new()));
+        line.push(utils::span(glyphs.vertical.to_string(), theme.border));
+        lines.push(line);
+    }
+    if shown.is_empty() {
+        let line = format!("{}{}{}", glyphs.vertical, " ".repeat(inner_width), glyphs.vertical);
+        lines.push(vec![utils::span(line, theme.border)]);
+    }
+    lines.push(edge(glyphs.bottom_left, glyphs.bottom_right, range.end < shown.len()));
+
+    let column = anchor.1.min(width - inner_width - 2);
+    if rows.len() < start + lines.len() {
+        rows.resize(start + lines.len(), Vec::new());
+    }
+    for (i, value) in shown[range].iter().copied().enumerate() {
+        let columns = column..column + inner_width + 2;
+        view.hits.push(Hit { row: start + 1 + i, columns, item: menu.selected, value: Some(value), popup: true });
+    }
+    for (i, line) in lines.into_iter().enumerate() {
+        let row = std::mem::take(&mut rows[start + i]);
+        rows[start + i] = utils::overlay(row, column, line);
+    }
+}
+
+/// Move the areas down by rows and right by columns.
+fn shift(hits: &mut [Hit], rows: usize, columns: usize) {
+    for hit in hits {
+        hit.row += rows;
+        hit.columns = hit.columns.start + columns..hit.columns.end + columns;
+    }
+}
+
+/// Text laid out to the width like the items, one vec of spans per row.
+fn text_rows(menu: &TerminalMenuStruct, text: &str, style: style::ContentStyle, width: usize) -> Vec<Vec<Span>> {
+    let spans = vec![utils::span(text, style)];
+    match overflow(menu) {
+        Overflow::Truncate => vec![utils::truncate(spans, width)],
+        Overflow::Wrap => utils::wrap(spans, width, 0),
+    }
+}
+
+/// Where the value of the item starts, counted from the end of the cursor.
+fn value_column(menu: &TerminalMenuStruct, index: usize, width: usize) -> usize {
+    match menu.value_alignment.unwrap_or(ValueAlignment::AfterNames) {
+        ValueAlignment::AfterNames => menu.longest_name + 5,
+        ValueAlignment::Column(column) => column.max(name_width(&menu.items[index]) + 1),
+        ValueAlignment::Right => {
+            let unaligned = utils::spans_width(&item_spans(menu, index, 0));
+            width.saturating_sub(unaligned) + name_width(&menu.items[index])
+        }
+    }
+}
+
+/// The item laid out to the width, one vec of spans per row.
+fn item_rows(menu: &TerminalMenuStruct, index: usize, width: usize) -> Vec<Vec<Span>> {
+    let spans = item_spans(menu, index, value_column(menu, index, width));
+    match overflow(menu) {
+        Overflow::Truncate => vec![utils::truncate(spans, width)],
+        Overflow::Wrap => utils::wrap(spans, width, 2),
+    }
+}
+
+/// The items, with the preview pane next to or below them if the menu has one.
+fn body(menu: &TerminalMenuStruct, view: &mut View, theme: &Theme, width: usize, height: usize) -> Vec<Vec<Span>> {
+    if miller_columns(menu) {
+        return miller(menu, view, theme, width, height);
+    }
+    let (position, preview) = match &menu.preview {
+        Some(preview) => preview,
+        None => return item_area(menu, view, theme, width, height)
+    };
+    let text = preview_text(menu, view, preview);
+
+    match position {
+        PreviewPosition::Right => {
+            if width < 5 {
+                return item_area(menu, view, theme, width, height);
+            }
+            let list_width = width / 2;
+            let items = item_area(menu, view, theme, list_width, height);
+            let pane = preview_rows(menu, theme, &text, width - list_width - 3, height);
+            (0..items.len().max(pane.len())).map(|i| {
+                let mut row = items.get(i).cloned().unwrap_or_default();
+                let gap = list_width.saturating_sub(utils::spans_width(&row));
+                row.push(utils::span(" ".repeat(gap), style::ContentStyle::new()));
+                row.push(utils::span(" │ ", theme.border));
+                row.extend(pane.get(i).cloned().unwrap_or_default());
+                row
+            }).collect()
+        }
+        PreviewPosition::Bottom => {
+            if height < 3 {
+                return item_area(menu, view, theme, width, height);
+            }
+            let mut rows = item_area(menu, view, theme, width, height / 2);
+            rows.push(vec![utils::span("─".repeat(width), theme.border)]);
+            rows.extend(preview_rows(menu, theme, &text, width, height - rows.len()));
+            rows
+        }
+    }
+}
+
+/// Ask for the preview of the selected item, returning it if it is ready.
+fn preview_text(menu: &TerminalMenuStruct, view: &mut View, preview: &Preview) -> String {
+    preview.request(menu.selected, &menu.items[menu.selected].name);
+    let text = preview.get(menu.selected);
+    view.preview = text.as_ref().map(|_| menu.selected);
+    text.unwrap_or_default()
+}
+
+/// The parent menu, the items and the items of the selected submenu side by side.
+/// When the selected item is not a submenu the last column shows the preview, if there is one.
+fn miller(menu: &TerminalMenuStruct, view: &mut View, theme: &Theme, width: usize, height: usize) -> Vec<Vec<Span>> {
+    if width < 12 {
+        return item_area(menu, view, theme, width, height);
+    }
+    let side_width = width / 4;
+    let middle_width = width - side_width * 2 - 6;
+    let parent = match &view.parent_column {
+        Some(parent) => column_rows(parent, theme, side_width, height),
+        None => Vec::new()
+    };
+    let items = item_area(menu, view, theme, middle_width, height);
+    shift(&mut view.hits, 0, side_width + 3);
+    view.anchor.1 += side_width + 3;
+    let next = match (&menu.items[menu.selected].kind, &menu.preview) {
+        (TMIKind::Submenu(submenu), _) => column_rows(&column(&submenu.read().unwrap(), theme), theme, side_width, height),
+        (_, Some((_, preview))) => preview_rows(menu, theme, &preview_text(menu, view, preview), side_width, height),
+        _ => Vec::new()
+    };
+
+    let no_style = style::ContentStyle::new();
+    (0..parent.len().max(items.len()).max(next.len())).map(|i| {
+        let mut row = Vec::new();
+        for (column, column_width) in [(&parent, side_width), (&items, middle_width)] {
+            let cell = column.get(i).cloned().unwrap_or_default();
+            let gap = column_width.saturating_sub(utils::spans_width(&cell));
+            row.extend(cell);
+            row.push(utils::span(" ".repeat(gap), no_style));
+            row.push(utils::span(" │ ", theme.border));
+        }
+        row.extend(next.get(i).cloned().unwrap_or_default());
+        row
+    }).collect()
+}
+
+/// The names of the items of the menu, for drawing them as a column next to another menu.
+fn column(menu: &TerminalMenuStruct, theme: &Theme) -> Column {
+    let names = menu.items.iter().map(|item| {
+        let style = match item.kind {
+            TMIKind::Label => merge(theme.label_text, item.style),
+            _ => merge(theme.name, item.style)
+        };
+        if item.spans.is_empty() {
+            vec![utils::span(item.name.as_str(), style)]
+        } else {
+            item.spans.iter().map(|a| utils::span(a.content().as_str(), merge(style, *a.style()))).collect()
+        }
+    }).collect();
+    Column { names, selected: menu.selected }
+}
+
+/// The visible names of the column with the selected one highlighted.
+fn column_rows(column: &Column, theme: &Theme, width: usize, height: usize) -> Vec<Vec<Span>> {
+    scroll(0, column.selected, column.names.len(), height, 0).map(|i| {
+        let mut name = column.names[i].clone();
+        if i == column.selected {
+            name = name.into_iter().map(|a| utils::span(a.content().as_str(), merge(*a.style(), theme.selected))).collect();
+        }
+        utils::truncate(name, width)
+    }).collect()
+}
+
+/// The lines of the preview laid out to the width, at most height rows.
+fn preview_rows(menu: &TerminalMenuStruct, theme: &Theme, text: &str, width: usize, height: usize) -> Vec<Vec<Span>> {
+    text.lines()
+        .map(|line| line.replace('\t', "    ").chars().filter(|c| !c.is_control()).collect::<String>())
+        .flat_map(|line| text_rows(menu, &line, theme.name, width))
+        .take(height)
+        .collect()
+}
+
+/// The rows of the visible items with scroll indicators, the scrollbar and the position counter.
+fn item_area(menu: &TerminalMenuStruct, view: &mut View, theme: &Theme, width: usize, height: usize) -> Vec<Vec<Span>> {
+    let no_style = style::ContentStyle::new();
+    let first_top = view.top;
+    let (mut range, mut total, mut rows) = visible(menu, view, height, width);
+    let mut clipped = range.len() < total;
+    let counter = menu.position_counter.unwrap_or(false);
+    if clipped || counter {
+        let scrollbar = clipped && menu.scrollbar.unwrap_or(false);
+        let reserved = if clipped { 2 } else { 1 };
+        let width = if scrollbar { width.saturating_sub(2) } else { width };
+        if height <= reserved || width == 0 {
+            return Vec::new();
+        }
+        view.top = first_top;
+        (range, total, rows) = visible(menu, view, height - reserved, width);
+        clipped = range.len() < total;
+        if scrollbar {
+            let thumb_len = (range.len() * rows.len() / total).max(1);
+            let thumb_start = if range.end == total {
+                rows.len() - thumb_len
+            } else {
+                (range.start * rows.len() / total).min(rows.len() - thumb_len)
+            };
+            for (i, row) in rows.iter_mut().enumerate() {
+                let bar = if (thumb_start..thumb_start + thumb_len).contains(&i) { "█" } else { "│" };
+                row.push(utils::span(" ".repeat(width - utils::spans_width(row) + 1), no_style));
+                row.push(utils::span(bar, theme.border));
+            }
+        }
+    }
+
+    let mut area = Vec::new();
+    view.range = range.clone();
+    if clipped {
+        view.anchor.0 += 1;
+        shift(&mut view.hits, 1, 0);
+        if range.start > 0 {
+            area.push(vec![utils::span(theme.more_above.as_str(), theme.header)]);
+        } else {
+            area.push(Vec::new());
+        }
+    }
+    area.extend(rows);
+    if clipped || counter {
+        let mut bottom = Vec::new();
+        if clipped && range.end < total {
+            bottom.push(utils::span(theme.more_below.as_str(), theme.header));
+        }
+        if counter {
+            let (position, total) = position(menu);
+            let counter = format!("{}/{}", position, total);
+            let gap = width.saturating_sub(utils::spans_width(&bottom) + utils::width(&counter));
+            bottom.push(utils::span(" ".repeat(gap), no_style));
+            bottom.push(utils::span(counter, theme.header));
+        }
+        area.push(utils::truncate(bottom, width));
+    }
+    area
+}
+
+/// Position of the selected item among the selectable items, and their amount.
+fn position(menu: &TerminalMenuStruct) -> (usize, usize) {
+    let selectable: Vec<usize> = shown_items(menu).into_iter().filter(|a| selectable(menu, *a)).collect();
+    (
+        selectable.iter().position(|a| *a == menu.selected).map_or(0, |a| a + 1),
+        selectable.len()
+    )
+}
+
+/// The visible part of the items laid out in the given size.
+/// Returns the range of visible rows of the layout, the amount of rows in the layout and the visible rows.
+fn visible(menu: &TerminalMenuStruct, view: &mut View, rows: usize, width: usize) -> (std::ops::Range<usize>, usize, Vec<Vec<Span>>) {
+    if menu.grid {
+        view.grid = grid(menu, width);
+        let selected = view.grid.iter().position(|a| a.iter().any(|b| b.0 == menu.selected)).unwrap_or(0);
+        let range = scroll(view.top, selected, view.grid.len(), rows, scrolloff(menu));
+        view.top = range.start;
+        let rows = view.grid[range.clone()].iter().map(|row| grid_row(menu, row)).collect();
+        view.hits.clear();
+        for (row, cells) in view.grid[range.clone()].iter().enumerate() {
+            let mut column = 0;
+            for (item, width) in cells {
+                view.hits.push(Hit { row, columns: column..column + width, item: *item, value: None, popup: false });
+                column += width;
+            }
+        }
+        if menu.popup.is_some() {
+            let row = &view.grid[selected];
+            let column: usize = row.iter().take_while(|a| a.0 != menu.selected).map(|a| a.1).sum();
+            let name_end = utils::width(&theme(menu).cursor) + name_width(&menu.items[menu.selected]);
+            view.anchor = (selected - range.start, column + name_end + 1);
+        }
+        (range, view.grid.len(), rows)
+    } else {
+        view.grid.clear();
+        let shown = shown_items(menu);
+        let (range, rows, owners) = visible_rows(menu, &shown, &mut view.top, rows, width);
+        view.hits = owners.iter().enumerate()
+            .map(|(row, item)| Hit { row, columns: 0..width, item: *item, value: None, popup: false })
+            .collect();
+        for &i in &shown[range.clone()] {
+            if let (TMIKind::List { values, .. }, Some(row)) = (&menu.items[i].kind, owners.iter().position(|a| *a == i)) {
+                // the values are on the first row of the item, unless they wrap
+                let mut column = utils::width(&theme(menu).cursor) + value_column(menu, i, width).max(name_width(&menu.items[i]));
+                for (value, text) in values.iter().enumerate() {
+                    let end = (column + utils::width(text) + 2).min(width);
+                    view.hits.push(Hit { row, columns: column..end, item: i, value: Some(value), popup: false });
+                    column = end;
+                }
+            }
+        }
+        if menu.popup.is_some() {
+            let row = owners.iter().position(|a| *a == menu.selected).unwrap_or(0);
+            let value_column = value_column(menu, menu.selected, width)
+                .max(name_width(&menu.items[menu.selected]));
+            view.anchor = (row, utils::width(&theme(menu).cursor) + value_column + 1);
+        }
+        (range, shown.len(), rows)
+    }
+}
+
+/// Flow the selectable items into columns that fit the width, labels get rows of their own.
+/// Returns the rows of the grid as the items in them with the widths of their columns.
+fn grid(menu: &TerminalMenuStruct, width: usize) -> Vec<Vec<(usize, usize)>> {
+    let mut rows = Vec::new();
+    let mut section = Vec::new();
+    for i in shown_items(menu) {
+        if let TMIKind::Label = menu.items[i].kind {
+            rows.extend(flow(menu, &section, width));
+            section.clear();
+            rows.push(vec![(i, width)]);
+        } else {
+            section.push(i);
+        }
+    }
+    rows.extend(flow(menu, &section, width));
+    rows
+}
+
+/// Flow the items into as many columns as fit the width, filling one column at a time.
+fn flow(menu: &TerminalMenuStruct, items: &[usize], width: usize) -> Vec<Vec<(usize, usize)>> {
+    let cell_widths: Vec<usize> = items.iter()
+        .map(|i| utils::spans_width(&item_spans(menu, *i, 0)) + 2)
+        .collect();
+    for columns in (1..=items.len()).rev() {
+        let row_count = items.len().div_ceil(columns);
+        let widths: Vec<usize> = cell_widths.chunks(row_count)
+            .map(|column| column.iter().copied().max().unwrap())
+            .collect();
+        if widths.iter().sum::<usize>() <= width + 2 || columns == 1 {
+            let widths: Vec<usize> = widths.iter().map(|a| (*a).min(width)).collect();
+            return (0..row_count).map(|row| {
+                items.chunks(row_count)
+                    .zip(&widths)
+                    .filter_map(|(column, width)| column.get(row).map(|i| (*i, *width)))
+                    .collect()
+            }).collect();
+        }
+    }
+    Vec::new()
+}
+
+/// A row of the grid, every item cut or padded to the width of its column.
+fn grid_row(menu: &TerminalMenuStruct, row: &[(usize, usize)]) -> Vec<Span> {
+    let mut spans = Vec::new();
+    for (i, width) in row {
+        let cell = utils::truncate(item_spans(menu, *i, 0), *width);
+        let padding = width.saturating_sub(utils::spans_width(&cell));
+        spans.extend(cell);
+        spans.push(utils::span(" ".repeat(padding), style::ContentStyle::new()));
+    }
+    utils::trim_end(spans)
+}
+
+/// The shown items from the top one on that fit in the given amount of rows, and their rows.
+/// Moves the top item to keep the selected item visible.
+/// The range and the top are positions in the shown items, the item of each row is also returned.
+fn visible_rows(menu: &TerminalMenuStruct, shown: &[usize], top: &mut usize, rows: usize, width: usize) -> (std::ops::Range<usize>, Vec<Vec<Span>>, Vec<usize>) {
+    if shown.is_empty() {
+        *top = 0;
+        return (0..0, Vec::new(), Vec::new());
+    }
+    let scrolloff = scrolloff(menu);
+    let selected = shown.iter().position(|a| *a == menu.selected).unwrap_or(0);
+    let mut items: VecDeque<(usize, Vec<Vec<Span>>)> = scroll(*top, selected, shown.len(), rows, scrolloff)
+        .map(|i| (i, item_rows(menu, shown[i], width)))
+        .collect();
+    while items.len() > 1 && items.iter().map(|a| a.1.len()).sum::<usize>() > rows {
+        // items take more than a row each, drop the ones the selected item can do without
+        let first = items.front().unwrap().0;
+        let last = items.back().unwrap().0;
+        if last > selected + scrolloff || first == selected {
+            items.pop_back();
+        } else {
+            items.pop_front();
+        }
+    }
+    let range = match (items.front(), items.back()) {
+        (Some(first), Some(last)) => first.0..last.0 + 1,
+        _ => selected..selected
+    };
+    *top = range.start;
+    let owners = items.iter().flat_map(|a| vec![shown[a.0]; a.1.len()]).take(rows).collect();
+    (range, items.into_iter().flat_map(|a| a.1).take(rows).collect(), owners)
+}
+
+/// Number of columns the name of the item takes up.
+fn name_width(item: &TerminalMenuItem) -> usize {
+    if item.spans.is_empty() {
+        utils::width(&item.name)
+    } else {
+        utils::spans_width(&item.spans)
+    }
+}
+
+/// The item as spans, with its value starting from the value column.
+fn item_spans(menu: &TerminalMenuStruct, index: usize, value_column: usize) -> Vec<Span> {
+    let theme = theme(menu);
+    let item = &menu.items[index];
+    let mut spans = Vec::new();
+    let (name_style, value_style) = if menu.selected == index {
+        spans.push(utils::span(theme.cursor.as_str(), theme.selected));
+        (theme.selected, theme.selected)
+    } else {
+        spans.push(utils::span(" ".repeat(utils::width(&theme.cursor)), style::ContentStyle::new()));
+        let name_style = match item.kind {
+            TMIKind::Label => theme.label_text,
+            _ => theme.name
+        };
+        (merge(name_style, item.style), merge(theme.value, item.style))
+    };
+
+    let mut name = if item.spans.is_empty() {
+        vec![utils::span(item.name.as_str(), name_style)]
+    } else if menu.selected == index {
+        // keep attributes such as bold, but color the row like any selected row
+        item.spans.iter().map(|a| utils::span(a.content().as_str(), merge(*a.style(), name_style))).collect()
+    } else {
+        item.spans.iter().map(|a| utils::span(a.content().as_str(), merge(name_style, *a.style()))).collect()
+    };
+    let styled_name: String = name.iter().map(|a| a.content().as_str()).collect();
+    // positions in the name are positions in the spans as long as the spans start with it
+    if styled_name.starts_with(&item.name) {
+        if let Some(mnemonic) = item.mnemonic {
+            name = utils::highlight(name, &[mnemonic], theme.mnemonic);
+        }
+        if let Some(found) = menu.query.as_ref().and_then(|a| fuzzy_match(a, &item.name)) {
+            name = utils::highlight(name, &found.positions, theme.matched);
+        }
+    }
+    spans.extend(name);
+    if !matches!(item.kind, TMIKind::Label | TMIKind::Button | TMIKind::BackButton | TMIKind::Submenu(_)) {
+        let padding = value_column.saturating_sub(name_width(item));
+        spans.push(utils::span(" ".repeat(padding), name_style));
     }
 
-    match &menu.items[index].kind {
+    match &item.kind {
         TMIKind::Label      |
         TMIKind::Button     |
         TMIKind::BackButton |
         TMIKind::Submenu(_) => {}
         TMIKind::List { values, selected } => {
-            for i in 0..values.len() {
+            for (i, value) in values.iter().enumerate() {
                 if i == *selected {
-                    queue!(
-                        stdout(),
-                        style::Print("["),
-                        style::Print(&values[i]),
-                        style::Print("]")
-                    ).unwrap();
+                    spans.push(utils::span(format!("[{}]", value), value_style));
                 } else {
-                    queue!(
-                        stdout(),
-                        style::Print(" "),
-                        style::Print(&values[i]),
-                        style::Print(" ")
-                    ).unwrap();
+                    spans.push(utils::span(format!(" {} ", value), value_style));
                 }
             }
         }
         TMIKind::Scroll { values, selected } => {
-            queue!(
-                stdout(),
-                style::Print(" "),
-                style::Print(values.iter().nth(*selected).unwrap()),
-            ).unwrap();
+            spans.push(utils::span(format!(" {}", values[*selected]), value_style));
         }
         TMIKind::String { value, .. } => {
-            queue!(
-                stdout(),
-                style::Print(" "),
-                style::Print(value)
-            ).unwrap();
+            spans.push(utils::span(format!(" {}", value), value_style));
         }
         TMIKind::Numeric { value, .. } => {
-            queue!(
-                stdout(),
-                style::Print(" "),
-                style::Print(value)
-            ).unwrap()
+            spans.push(utils::span(format!(" {}", value), value_style));
         }
     }
-
-    queue!(
-        stdout(),
-        style::ResetColor
-    ).unwrap();
-
+    spans
 }
 
 fn handle_input(menu: &TerminalMenu) {
@@ -163,71 +1193,349 @@ fn handle_input(menu: &TerminalMenu) {
         match crossterm::event::read().unwrap() {
             crossterm::event::Event::Key(key_event) => {
                 let mut menu_wr = menu.write().unwrap();
+                if menu_wr.popup.is_some() {
+                    handle_popup_input(&mut menu_wr, key_event);
+                    continue;
+                }
+                if menu_wr.query.is_none() && handle_hotkey(&mut menu_wr, key_event) {
+                    continue;
+                }
+                if handle_filter_input(&mut menu_wr, key_event) {
+                    continue;
+                }
                 let selected = menu_wr.selected;
-                use crossterm::event::KeyCode::*;
-                match key_event.code {
-                    Up    | Char('w') | Char('k') => {
+                let action = keymap(&menu_wr).action(&key_event);
+                if !selectable(&menu_wr, selected) && action != Some(Action::Cancel) {
+                    // nothing matches the filter
+                    continue;
+                }
+                match action {
+                    Some(Action::MoveUp) => {
                         let new = dec(&menu_wr, selected);
                         select(&mut menu_wr, new);
                     },
-                    Down  | Char('s') | Char('j') => {
+                    Some(Action::MoveDown) => {
                         let new = inc(&menu_wr, selected);
                         select(&mut menu_wr, new);
                     },
-                    Left  | Char('a') | Char('h') => dec_value(&mut menu_wr),
-                    Right | Char('d') | Char('l') => inc_value(&mut menu_wr),
-                    Enter | Char(' ') => handle_enter(&mut menu_wr),
-                    Esc   | Char('q') => {
+                    Some(Action::Decrement) => {
+                        if miller_columns(&menu_wr) && !changeable(&menu_wr) {
+                            // go back like a back button, but only from a submenu
+                            if menu_wr.view.parent_column.is_some() {
+                                menu_wr.active = false;
+                            }
+                        } else if menu_wr.grid && !changeable(&menu_wr) {
+                            grid_move(&mut menu_wr, false);
+                        } else {
+                            dec_value(&mut menu_wr);
+                        }
+                    },
+                    Some(Action::Increment) => {
+                        if miller_columns(&menu_wr) && !changeable(&menu_wr) {
+                            if let TMIKind::Submenu(_) = menu_wr.items[selected].kind {
+                                handle_enter(&mut menu_wr);
+                            }
+                        } else if menu_wr.grid && !changeable(&menu_wr) {
+                            grid_move(&mut menu_wr, true);
+                        } else {
+                            inc_value(&mut menu_wr);
+                        }
+                    },
+                    Some(Action::Activate) => handle_enter(&mut menu_wr),
+                    // handled with the rest of the filter keys
+                    Some(Action::Filter) => {}
+                    Some(Action::Cancel) => {
                         menu_wr.active = false;
                         menu_wr.exit = menu_wr.name.clone();
                         menu_wr.canceled = true;
                         return;
                     },
-                    _ => {}
+                    None => {}
                 }
             }
+            event::Event::Mouse(mouse_event) => {
+                let mut menu_wr = menu.write().unwrap();
+                handle_mouse(&mut menu_wr, mouse_event);
+            }
             event::Event::Resize(_, _) => {
-                print(&mut menu.write().unwrap());
+                let mut menu_wr = menu.write().unwrap();
+                if let PrintState::Big(frame) = &mut menu_wr.printed {
+                    // the terminal may have reflowed, draw everything again
+                    frame.clear();
+                }
+                print(&mut menu_wr);
             }
             _ => {}
         }
     }
 }
 
-fn select(menu: &mut TerminalMenuStruct, index: usize) {
-    //let old_active = menu.selected;
+/// Select the item with the key as its hotkey, pressing it if it is a button.
+/// Returns false if no selectable item has the key, or if the key does something in the keymap.
+fn handle_hotkey(menu: &mut TerminalMenuStruct, key: KeyEvent) -> bool {
+    let c = match key.code {
+        KeyCode::Char(c) if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => c,
+        _ => return false
+    };
+    if keymap(menu).action(&key).is_some() {
+        return false;
+    }
+    let c = c.to_lowercase().next().unwrap_or(c);
+    let index = match (0..menu.items.len()).find(|i| menu.items[*i].hotkey == Some(c) && selectable(menu, *i)) {
+        Some(index) => index,
+        None => return false
+    };
     menu.selected = index;
-    print_big(menu);
-    stdout().flush().unwrap();
+    if let TMIKind::Button | TMIKind::BackButton = menu.items[index].kind {
+        handle_enter(menu);
+    } else {
+        print(menu);
+    }
+    true
 }
 
-fn inc(menu: &TerminalMenuStruct, mut index: usize) -> usize {
-    index += 1;
-    if index == menu.items.len() {
-        index = 0;
+/// Keys that start, edit and clear the filter. Returns false if the key is not one of them.
+fn handle_filter_input(menu: &mut TerminalMenuStruct, key: KeyEvent) -> bool {
+    let typed = !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+    let action = keymap(menu).action(&key);
+    let filter = filter(menu);
+    match (&mut menu.query, key.code) {
+        (Some(query), KeyCode::Char(c)) if typed => query.push(c),
+        (Some(query), KeyCode::Backspace) => {
+            query.pop();
+        }
+        (Some(_), _) if action == Some(Action::Cancel) => menu.query = None,
+        (None, _) if action == Some(Action::Filter) && filter => menu.query = Some(String::new()),
+        (None, KeyCode::Char(c)) if typed && action.is_none() && filter => menu.query = Some(c.to_string()),
+        _ => return false
     }
-    if let TMIKind::Label = menu.items[index].kind {
-        inc(menu, index)
-    } else {
-        index
+    // select the best match, or keep the selection when there is no filter
+    if menu.query.as_ref().is_some_and(|a| !a.is_empty()) || !selectable(menu, menu.selected) {
+        menu.selected = shown_items(menu).into_iter().find(|a| selectable(menu, *a)).unwrap_or(menu.selected);
     }
+    menu.view.top = 0;
+    print(menu);
+    true
 }
 
-fn dec(menu: &TerminalMenuStruct, mut index: usize) -> usize {
-    if index == 0 {
-         index = menu.items.len() - 1;
-    } else {
-        index -= 1
+/// Keys while the values of the selected item are shown in a popup.
+/// Typing filters the values, best matches first, esc clears the filter before closing the popup.
+fn handle_popup_input(menu: &mut TerminalMenuStruct, key: KeyEvent) {
+    if !matches!(menu.items[menu.selected].kind, TMIKind::Scroll { .. } | TMIKind::List { .. }) {
+        menu.popup = None;
+        return;
+    }
+    let action = keymap(menu).action(&key);
+    let typed = !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+    match key.code {
+        KeyCode::Char(c) if c != ' ' && typed => menu.popup_query.push(c),
+        KeyCode::Backspace => {
+            menu.popup_query.pop();
+        }
+        _ if action == Some(Action::Cancel) && !menu.popup_query.is_empty() => menu.popup_query.clear(),
+        _ => {
+            let shown = popup_values(menu);
+            let len = shown.len();
+            let position = shown.iter().position(|a| Some(*a) == menu.popup);
+            match (action, position) {
+                (Some(Action::MoveUp), Some(position)) => menu.popup = Some(shown[(position + len - 1) % len]),
+                (Some(Action::MoveDown), Some(position)) => menu.popup = Some(shown[(position + 1) % len]),
+                (Some(Action::Activate), Some(position)) => pick(menu, shown[position]),
+                (Some(Action::Cancel), _) => menu.popup = None,
+                _ => return
+            }
+            print(menu);
+            return;
+        }
+    }
+    // select the best match, or keep the selection when there is no filter
+    if !menu.popup_query.is_empty() {
+        if let Some(best) = popup_values(menu).first() {
+            menu.popup = Some(*best);
+        }
+    }
+    menu.view.popup_top = 0;
+    print(menu);
+}
+
+/// Set the value of the selected scroll or list item and close the popup.
+fn pick(menu: &mut TerminalMenuStruct, value: usize) {
+    if let TMIKind::Scroll { selected, .. } |
+           TMIKind::List   { selected, .. } = &mut menu.items[menu.selected].kind {
+        *selected = value;
+    }
+    menu.popup = None;
+}
+
+/// Clicks select items and values, double clicks press items and the wheel scrolls.
+fn handle_mouse(menu: &mut TerminalMenuStruct, mouse_event: event::MouseEvent) {
+    use event::{MouseButton, MouseEventKind};
+    let (row, column) = (mouse_event.row as usize, mouse_event.column as usize);
+    let hit = menu.view.hits.iter().rev()
+        .find(|a| a.row == row && a.columns.contains(&column))
+        .cloned();
+    match mouse_event.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            let last_click = menu.view.last_click.take();
+            if menu.popup.is_some() {
+                match hit {
+                    Some(Hit { popup: true, value: Some(value), .. }) => pick(menu, value),
+                    _ => menu.popup = None
+                }
+                print(menu);
+                return;
+            }
+            let hit = match hit {
+                Some(hit) if !matches!(menu.items[hit.item].kind, TMIKind::Label) => hit,
+                _ => return
+            };
+            if let Some(value) = hit.value {
+                if let TMIKind::List { selected, .. } = &mut menu.items[hit.item].kind {
+                    *selected = value;
+                }
+                select(menu, hit.item);
+                return;
+            }
+            let double_click = last_click.is_some_and(|(item, time)| item == hit.item && time.elapsed() < *DOUBLE_CLICK);
+            if double_click {
+                menu.selected = hit.item;
+                handle_enter(menu);
+            } else {
+                menu.view.last_click = Some((hit.item, Instant::now()));
+                select(menu, hit.item);
+            }
+        }
+        MouseEventKind::ScrollDown => wheel(menu, true),
+        MouseEventKind::ScrollUp => wheel(menu, false),
+        _ => {}
     }
-    if let TMIKind::Label = menu.items[index].kind {
-        dec(menu, index)
+}
+
+/// Scroll the items by one, moving the selection only if it would leave the scrolloff.
+fn wheel(menu: &mut TerminalMenuStruct, down: bool) {
+    if menu.popup.is_some() {
+        let shown = popup_values(menu);
+        if let Some(position) = shown.iter().position(|a| Some(*a) == menu.popup) {
+            let position = if down { (position + 1).min(shown.len() - 1) } else { position.saturating_sub(1) };
+            menu.popup = Some(shown[position]);
+        }
+    } else if menu.grid {
+        let new = if down { inc(menu, menu.selected) } else { dec(menu, menu.selected) };
+        menu.selected = new;
     } else {
-        index
+        // the range is of positions in the shown items
+        let shown = shown_items(menu);
+        let range = menu.view.range.clone();
+        let scrolloff = scrolloff(menu);
+        let selected = shown.iter().position(|a| *a == menu.selected).unwrap_or(0);
+        let selectable = |i: &usize| !matches!(menu.items[shown[*i]].kind, TMIKind::Label);
+        let len = shown.len();
+        if down && range.end < len {
+            menu.view.top = range.start + 1;
+            let first = (menu.view.top + scrolloff).min(len - 1);
+            if selected < first {
+                menu.selected = (first..len).find(selectable).map_or(menu.selected, |a| shown[a]);
+            }
+        } else if !down && range.start > 0 {
+            menu.view.top = range.start - 1;
+            let last = range.end.saturating_sub(2 + scrolloff);
+            if selected > last {
+                menu.selected = (0..=last).rev().find(selectable).map_or(menu.selected, |a| shown[a]);
+            }
+        }
+    }
+    print(menu);
+}
+
+fn select(menu: &mut TerminalMenuStruct, index: usize) {
+    menu.selected = index;
+    print(menu);
+}
+
+/// Returns true if the value of the selected item can be changed with left and right.
+fn changeable(menu: &TerminalMenuStruct) -> bool {
+    matches!(menu.items[menu.selected].kind, TMIKind::Scroll { .. } | TMIKind::List { .. } | TMIKind::Numeric { .. })
+}
+
+/// Select the item in the next or the previous column of the grid, if there is one.
+fn grid_move(menu: &mut TerminalMenuStruct, right: bool) {
+    let target = menu.view.grid.iter().find_map(|row| {
+        let column = row.iter().position(|a| a.0 == menu.selected)?;
+        let column = if right { column + 1 } else { column.checked_sub(1)? };
+        row.get(column).map(|a| a.0)
+    });
+    if let Some(target) = target {
+        select(menu, target);
+    }
+}
+
+/// The next selectable item in the order the items are shown, or the item itself if there is none.
+fn inc(menu: &TerminalMenuStruct, index: usize) -> usize {
+    let shown = shown_items(menu);
+    let len = shown.len();
+    match shown.iter().position(|a| *a == index) {
+        Some(position) => (1..=len)
+            .map(|a| shown[(position + a) % len])
+            .find(|a| selectable(menu, *a))
+            .unwrap_or(index),
+        None => index
+    }
+}
+
+/// The previous selectable item in the order the items are shown, or the item itself if there is none.
+fn dec(menu: &TerminalMenuStruct, index: usize) -> usize {
+    let shown = shown_items(menu);
+    let len = shown.len();
+    match shown.iter().position(|a| *a == index) {
+        Some(position) => (1..=len)
+            .map(|a| shown[(position + len - a) % len])
+            .find(|a| selectable(menu, *a))
+            .unwrap_or(index),
+        None => index
     }
 }
 
+/// Returns true if the item is shown, which it is unless the items are filtered and it doesn't match.
+fn shown(menu: &TerminalMenuStruct, index: usize) -> bool {
+    match &menu.query {
+        Some(query) => {
+            !matches!(menu.items[index].kind, TMIKind::Label) &&
+                fuzzy_match(query, &menu.items[index].name).is_some()
+        }
+        None => true
+    }
+}
+
+/// The shown items in the order they are shown, best matches of the filter first.
+fn shown_items(menu: &TerminalMenuStruct) -> Vec<usize> {
+    match &menu.query {
+        Some(query) => {
+            let names: Vec<&str> = menu.items.iter().map(|a| a.name.as_str()).collect();
+            fuzzy_rank(query, &names).into_iter()
+                .map(|a| a.0)
+                .filter(|a| !matches!(menu.items[*a].kind, TMIKind::Label))
+                .collect()
+        }
+        None => (0..menu.items.len()).collect()
+    }
+}
+
+/// The values of the selected item in the order the popup shows them, best matches of its filter first.
+fn popup_values(menu: &TerminalMenuStruct) -> Vec<usize> {
+    match &menu.items[menu.selected].kind {
+        TMIKind::Scroll { values, .. } |
+        TMIKind::List   { values, .. } => fuzzy_rank(&menu.popup_query, values).into_iter().map(|a| a.0).collect(),
+        _ => Vec::new()
+    }
+}
+
+fn selectable(menu: &TerminalMenuStruct, index: usize) -> bool {
+    !matches!(menu.items[index].kind, TMIKind::Label) && shown(menu, index)
+}
+
 fn handle_enter(menu: &mut TerminalMenuStruct) {
-    let _item_count = menu.items.len();
+    let backend = backend(menu);
+    let mouse = mouse(menu);
     match &mut menu.items[menu.selected].kind {
         TMIKind::Button => {
             menu.exit = menu.name.clone();
@@ -236,77 +1544,23 @@ fn handle_enter(menu: &mut TerminalMenuStruct) {
         TMIKind::BackButton => {
             menu.active = false;
         }
-        TMIKind::Scroll { selected, values } |
-        TMIKind::List { selected, values } => {
-            let temp_menu =
-                crate::menu(values.iter().enumerate().map(|(i, s)|
-                    if i == *selected {
-                        back_button(s).colorize(style::Color::Green)
-                    } else {
-                        back_button(s)
-                    }
-                ).collect());
-            temp_menu.write().unwrap().selected = *selected;
-
-            crate::run(&temp_menu);
-
-            *selected = temp_menu.read().unwrap().selected;
-
-            menu.printed = PrintState::None;
+        TMIKind::Scroll { selected, .. } |
+        TMIKind::List { selected, .. } => {
+            menu.popup = Some(*selected);
+            menu.popup_query.clear();
+            menu.view.popup_top = 0;
             print(menu);
-            terminal::enable_raw_mode().unwrap();
-            execute!(
-                stdout(),
-                cursor::Hide
-            ).unwrap();
         }
         TMIKind::String { value, allow_empty } => {
-            queue!(
-                stdout(),
-                cursor::MoveToNextLine(100)
-            ).unwrap();
-            print!(": ");
-            stdout().flush().unwrap();
-            terminal::disable_raw_mode().unwrap();
-            execute!(
-                stdout(),
-                cursor::Show,
-            ).unwrap();
-            let mut input = String::new();
-            stdin().read_line(&mut input).unwrap();
-            input = input.trim().to_owned();
-            terminal::enable_raw_mode().unwrap();
-            execute!(
-                stdout(),
-                cursor::Hide,
-            ).unwrap();
-            utils::unprint(1);
+            let input = read_line(&backend, &mut menu.printed, mouse, ": ");
             if *allow_empty || !input.is_empty() {
                 *value = input;
             }
             print(menu);
         }
         TMIKind::Numeric { value, step, min, max } => {
-            queue!(
-                stdout(),
-                cursor::MoveToNextLine(100)
-            ).unwrap();
-            utils::number_range_indicator(*step, *min, *max);
-            stdout().flush().unwrap();
-            terminal::disable_raw_mode().unwrap();
-            execute!(
-                stdout(),
-                cursor::Show,
-            ).unwrap();
-            let mut input = String::new();
-            stdin().read_line(&mut input).unwrap();
-            terminal::enable_raw_mode().unwrap();
-            execute!(
-                stdout(),
-                cursor::Hide,
-            ).unwrap();
-            utils::unprint(1);
-            if let Ok(input) = input.trim().parse() {
+            let input = read_line(&backend, &mut menu.printed, mouse, &utils::number_range_indicator(*step, *min, *max));
+            if let Ok(input) = input.parse() {
                 if utils::value_valid(input, *step, *min, *max) {
                     *value = input;
                 }
@@ -314,20 +1568,35 @@ fn handle_enter(menu: &mut TerminalMenuStruct) {
             print(menu);
         }
         TMIKind::Submenu(submenu) => {
-            crate::run(submenu);
+            let submenu = submenu.clone();
+            inherit(menu, &submenu);
+            if miller_columns(menu) {
+                submenu.write().unwrap().view.parent_column = Some(column(menu, &theme(menu)));
+            }
 
-            if let Some(exit_menu) = &submenu.clone().read().unwrap().exit {
+            if shares_screen(&submenu.read().unwrap()) {
+                // the submenu draws over the frame of the menu, so the screen doesn't flash
+                submenu.write().unwrap().printed = std::mem::replace(&mut menu.printed, PrintState::None);
+            } else {
+                erase(menu);
+            }
+            crate::run(&submenu);
+            {
+                let mut submenu = submenu.write().unwrap();
+                if shares_screen(&submenu) {
+                    menu.printed = std::mem::replace(&mut submenu.printed, PrintState::None);
+                }
+                submenu.view.parent_column = None;
+            }
+
+            let submenu = submenu.read().unwrap();
+            if let Some(exit_menu) = &submenu.exit {
                 menu.exit = Some(exit_menu.clone());
-                menu.canceled = submenu.read().unwrap().canceled;
+                menu.canceled = submenu.canceled;
                 menu.active = false;
             } else {
-                menu.printed = PrintState::None;
                 print(menu);
-                terminal::enable_raw_mode().unwrap();
-                execute!(
-                    stdout(),
-                    cursor::Hide
-                ).unwrap();
+                enter_raw_mode(&backend, mouse);
             }
         }
         _ => {}
@@ -382,4 +1651,39 @@ fn dec_value(menu: &mut TerminalMenuStruct) {
         _ => return
     }
     print(menu);
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::scroll;
+
+    #[test]
+    fn scroll_without_rows_is_empty() {
+        assert_eq!(scroll(0, 0, 0, 10, 3), 0..0);
+        assert!(scroll(0, 5, 10, 0, 3).is_empty());
+    }
+
+    #[test]
+    fn scroll_shows_everything_that_fits() {
+        assert_eq!(scroll(5, 7, 8, 10, 3), 0..8);
+    }
+
+    #[test]
+    fn scroll_keeps_the_viewport_away_from_the_edges() {
+        assert_eq!(scroll(0, 6, 100, 10, 3), 0..10);
+        assert_eq!(scroll(0, 7, 100, 10, 3), 1..11);
+        assert_eq!(scroll(10, 12, 100, 10, 3), 9..19);
+    }
+
+    #[test]
+    fn scroll_stops_at_the_ends() {
+        assert_eq!(scroll(0, 99, 100, 10, 3), 90..100);
+        assert_eq!(scroll(50, 0, 100, 10, 3), 0..10);
+        assert_eq!(scroll(500, 50, 100, 10, 0), 50..60);
+    }
+
+    #[test]
+    fn scroll_limits_scrolloff_to_half_the_rows() {
+        assert_eq!(scroll(0, 50, 100, 5, 10), 48..53);
+    }
+}
@@ -0,0 +1,147 @@
+/// Every matched character.
+const SCORE_MATCH: i32 = 16;
+/// Starting a gap between matched characters, and making it longer.
+const PENALTY_GAP_START: i32 = 3;
+const PENALTY_GAP_EXTENSION: i32 = 1;
+/// Matching the start of a word.
+const BONUS_BOUNDARY: i32 = 8;
+/// Matching an uppercase letter after a lowercase one, or a digit after a letter.
+const BONUS_CAMEL_CASE: i32 = 7;
+/// Matching right after the previous matched character.
+const BONUS_CONSECUTIVE: i32 = 4;
+/// The bonus of the first character of the query counts this many times.
+const FIRST_CHAR_MULTIPLIER: i32 = 2;
+
+/// How a query matches a text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// Higher is better.
+    pub score: i32,
+    /// Indices of the matched characters of the text, in order.
+    pub positions: Vec<usize>,
+}
+
+/// Match the characters of the query in order anywhere in the text, like fzf.
+/// Matches at the start of words and runs of matched characters score higher, gaps score lower.
+/// Case is ignored unless the query has uppercase letters. Empty queries match everything.
+/// # Example
+/// ```
+/// use terminal_menu::fuzzy_match;
+/// let save = fuzzy_match("sa", "Save as").unwrap();
+/// let pasta = fuzzy_match("sa", "pasta").unwrap();
+/// assert_eq!(save.positions, vec![0, 1]);
+/// assert!(save.score > pasta.score);
+/// assert!(fuzzy_match("xyz", "Save as").is_none());
+/// ```
+pub fn fuzzy_match(query: &str, text: &str) -> Option<FuzzyMatch> {
+    let case_sensitive = query.chars().any(char::is_uppercase);
+    let fold = |c: char| if case_sensitive { c } else { c.to_lowercase().next().unwrap_or(c) };
+    let query: Vec<char> = query.chars().map(fold).collect();
+    let original: Vec<char> = text.chars().collect();
+    let text: Vec<char> = original.iter().copied().map(fold).collect();
+    if query.is_empty() {
+        return Some(FuzzyMatch { score: 0, positions: Vec::new() });
+    }
+    let mut remaining = query.iter().peekable();
+    for c in &text {
+        if remaining.peek() == Some(&c) {
+            remaining.next();
+        }
+    }
+    if remaining.peek().is_some() {
+        return None;
+    }
+
+    let bonuses: Vec<i32> = (0..original.len()).map(|j| bonus(&original, j)).collect();
+    // scores[i][j] is the best score of the query up to i with its character i matched at j,
+    // from[i][j] is where character i - 1 was matched then
+    let mut scores = vec![vec![None; text.len()]; query.len()];
+    let mut from = vec![vec![0; text.len()]; query.len()];
+    for (i, q) in query.iter().enumerate() {
+        let multiplier = if i == 0 { FIRST_CHAR_MULTIPLIER } else { 1 };
+        // the best way to reach j over a gap, and where it comes from
+        let mut gap: Option<(i32, usize)> = None;
+        for j in 0..text.len() {
+            if i > 0 && j >= 2 {
+                gap = gap.map(|(score, k)| (score - PENALTY_GAP_EXTENSION, k));
+                if let Some(score) = scores[i - 1][j - 2] {
+                    let score = score - PENALTY_GAP_START;
+                    if gap.is_none_or(|a| score > a.0) {
+                        gap = Some((score, j - 2));
+                    }
+                }
+            }
+            if text[j] != *q {
+                continue;
+            }
+            let base = SCORE_MATCH + bonuses[j] * multiplier;
+            if i == 0 {
+                scores[i][j] = Some(base);
+                continue;
+            }
+            let consecutive = match j {
+                0 => None,
+                _ => scores[i - 1][j - 1].map(|a| (a + BONUS_CONSECUTIVE, j - 1))
+            };
+            let best = match (consecutive, gap) {
+                (Some(a), Some(b)) => Some(if a.0 >= b.0 { a } else { b }),
+                (a, b) => a.or(b)
+            };
+            if let Some((score, k)) = best {
+                scores[i][j] = Some(base + score);
+                from[i][j] = k;
+            }
+        }
+    }
+
+    let last = query.len() - 1;
+    let (mut j, score) = scores[last].iter().enumerate()
+        .filter_map(|(j, a)| a.map(|a| (j, a)))
+        .max_by_key(|a| a.1)?;
+    let mut positions = vec![j];
+    for i in (1..query.len()).rev() {
+        j = from[i][j];
+        positions.push(j);
+    }
+    positions.reverse();
+    Some(FuzzyMatch { score, positions })
+}
+
+/// The texts that match the query, best first, as their indices and matches.
+/// Equal scores put shorter texts first, then keep the order of the texts.
+/// An empty query matches every text and keeps them in order.
+/// # Example
+/// ```
+/// use terminal_menu::fuzzy_rank;
+/// let ranked = fuzzy_rank("ct", &["dictionary", "contact", "cat"]);
+/// let order: Vec<usize> = ranked.iter().map(|a| a.0).collect();
+/// assert_eq!(order, vec![2, 1, 0]);
+/// let ranked = fuzzy_rank("", &["medium", "small", "large"]);
+/// let order: Vec<usize> = ranked.iter().map(|a| a.0).collect();
+/// assert_eq!(order, vec![0, 1, 2]);
+/// ```
+pub fn fuzzy_rank<T: AsRef<str>>(query: &str, texts: &[T]) -> Vec<(usize, FuzzyMatch)> {
+    let mut ranked: Vec<(usize, FuzzyMatch)> = texts.iter().enumerate()
+        .filter_map(|(i, a)| fuzzy_match(query, a.as_ref()).map(|m| (i, m)))
+        .collect();
+    if query.is_empty() {
+        return ranked;
+    }
+    ranked.sort_by_key(|(i, m)| (-m.score, texts[*i].as_ref().chars().count(), *i));
+    ranked
+}
+
+/// The bonus of matching the character at the index.
+fn bonus(text: &[char], index: usize) -> i32 {
+    let c = text[index];
+    if !c.is_alphanumeric() {
+        return 0;
+    }
+    match index.checked_sub(1).map(|a| text[a]) {
+        None => BONUS_BOUNDARY,
+        Some(previous) if !previous.is_alphanumeric() => BONUS_BOUNDARY,
+        Some(previous) if previous.is_lowercase() && c.is_uppercase() => BONUS_CAMEL_CASE,
+        Some(previous) if previous.is_alphabetic() && c.is_numeric() => BONUS_CAMEL_CASE,
+        _ => 0
+    }
+}
@@ -0,0 +1,165 @@
+use std::fmt;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Something a key does in a menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Select the previous item.
+    MoveUp,
+    /// Select the next item.
+    MoveDown,
+    /// Change the value of the selected item to the previous one.
+    /// Moves left in grids and goes back in miller columns.
+    Decrement,
+    /// Change the value of the selected item to the next one.
+    /// Moves right in grids and opens submenus in miller columns.
+    Increment,
+    /// Press the selected item.
+    Activate,
+    /// Exit the menu without pressing anything.
+    /// Clears the filter first while the items are being filtered.
+    Cancel,
+    /// Start filtering the items, if the menu can be filtered.
+    Filter,
+}
+
+/// The keys of a menu and what they do.
+/// # Example
+/// ```
+/// use terminal_menu::{menu, button, mut_menu, KeyMap, Action};
+/// use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+/// let my_menu = menu(vec![
+///     button("Alice"),
+///     button("Bob")
+/// ]);
+/// // arrow keys, with x and ctrl+c to cancel
+/// mut_menu(&my_menu).set_keymap(KeyMap::arrows()
+///     .bind(KeyCode::Char('x'), Action::Cancel)
+///     .bind(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL), Action::Cancel)).unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyMap {
+    bindings: Vec<(KeyCode, KeyModifiers, Action)>,
+}
+
+impl KeyMap {
+
+    /// A keymap without any keys.
+    pub fn new() -> Self {
+        KeyMap {
+            bindings: Vec::new(),
+        }
+    }
+
+    /// Arrow keys, enter, esc and / to filter.
+    pub fn arrows() -> Self {
+        KeyMap::new()
+            .bind(KeyCode::Up, Action::MoveUp)
+            .bind(KeyCode::Down, Action::MoveDown)
+            .bind(KeyCode::Left, Action::Decrement)
+            .bind(KeyCode::Right, Action::Increment)
+            .bind(KeyCode::Enter, Action::Activate)
+            .bind(KeyCode::Esc, Action::Cancel)
+            .bind(KeyCode::Char('/'), Action::Filter)
+    }
+
+    /// Arrow keys and hjkl, enter and space, esc and q, and / to filter.
+    pub fn vim() -> Self {
+        KeyMap::arrows()
+            .bind(KeyCode::Char('k'), Action::MoveUp)
+            .bind(KeyCode::Char('j'), Action::MoveDown)
+            .bind(KeyCode::Char('h'), Action::Decrement)
+            .bind(KeyCode::Char('l'), Action::Increment)
+            .bind(KeyCode::Char(' '), Action::Activate)
+            .bind(KeyCode::Char('q'), Action::Cancel)
+    }
+
+    /// Arrow keys and ctrl+p, ctrl+n, ctrl+b, ctrl+f, enter, esc and ctrl+g, and / to filter.
+    pub fn emacs() -> Self {
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        KeyMap::arrows()
+            .bind(ctrl('p'), Action::MoveUp)
+            .bind(ctrl('n'), Action::MoveDown)
+            .bind(ctrl('b'), Action::Decrement)
+            .bind(ctrl('f'), Action::Increment)
+            .bind(ctrl('g'), Action::Cancel)
+    }
+
+    /// Make the key do the action, replacing what it did before.
+    /// Keys of an action are shown in the help bar in the order they were bound.
+    pub fn bind<K: Into<KeyEvent>>(mut self, key: K, action: Action) -> Self {
+        let key = key.into();
+        self = self.unbind(key);
+        self.bindings.push((key.code, key.modifiers, action));
+        self
+    }
+
+    /// Make the key do nothing.
+    pub fn unbind<K: Into<KeyEvent>>(mut self, key: K) -> Self {
+        let key = key.into();
+        self.bindings.retain(|a| (a.0, a.1) != (key.code, key.modifiers));
+        self
+    }
+
+    /// The action of the key, if it has one.
+    /// Shift is ignored for characters, since it is part of the character already.
+    pub fn action(&self, key: &KeyEvent) -> Option<Action> {
+        let modifiers = match key.code {
+            KeyCode::Char(_) => key.modifiers - KeyModifiers::SHIFT,
+            _ => key.modifiers
+        };
+        self.bindings.iter()
+            .find(|a| a.0 == key.code && a.1 == modifiers)
+            .map(|a| a.2)
+    }
+
+    /// The keys that do the action.
+    pub fn keys(&self, action: Action) -> Vec<KeyEvent> {
+        self.bindings.iter()
+            .filter(|a| a.2 == action)
+            .map(|a| KeyEvent::new(a.0, a.1))
+            .collect()
+    }
+
+}
+
+/// Arrow keys, wasd and hjkl, enter and space, esc and q, and / to filter.
+impl Default for KeyMap {
+    fn default() -> Self {
+        KeyMap::arrows()
+            .bind(KeyCode::Char('w'), Action::MoveUp)
+            .bind(KeyCode::Char('k'), Action::MoveUp)
+            .bind(KeyCode::Char('s'), Action::MoveDown)
+            .bind(KeyCode::Char('j'), Action::MoveDown)
+            .bind(KeyCode::Char('a'), Action::Decrement)
+            .bind(KeyCode::Char('h'), Action::Decrement)
+            .bind(KeyCode::Char('d'), Action::Increment)
+            .bind(KeyCode::Char('l'), Action::Increment)
+            .bind(KeyCode::Char(' '), Action::Activate)
+            .bind(KeyCode::Char('q'), Action::Cancel)
+    }
+}
+
+/// A hotkey of an item that would never do anything.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HotkeyError {
+    /// The key does the action in the keymap of the menu.
+    Bound { item: String, key: char, action: Action },
+    /// Two items of the same menu have the key.
+    Duplicate { items: (String, String), key: char },
+}
+
+impl fmt::Display for HotkeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HotkeyError::Bound { item, key, action } => {
+                write!(f, "hotkey {} of \"{}\" is bound to {:?} in the keymap", key, item, action)
+            }
+            HotkeyError::Duplicate { items, key } => {
+                write!(f, "hotkey {} is used by both \"{}\" and \"{}\"", key, items.0, items.1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HotkeyError {}
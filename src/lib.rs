@@ -1,13 +1,30 @@
 //! Display simple menus on the terminal!
 //! [Examples](https://gitlab.com/xamn/terminal-menu-rs/tree/master/examples)
 
+mod backend;
+mod color;
 mod fancy_menu;
+mod fuzzy;
+mod keymap;
+mod preview;
+mod status;
+mod theme;
 mod utils;
 
-use std::sync::{Arc, RwLock, RwLockWriteGuard};
+pub use backend::{Backend, Buffer, tty};
+pub use color::ColorSupport;
+pub use fuzzy::{fuzzy_match, fuzzy_rank, FuzzyMatch};
+pub use keymap::{Action, HotkeyError, KeyMap};
+pub use status::Status;
+pub use theme::Theme;
+use backend::SharedBackend;
+use preview::Preview;
+
+use std::fmt::Display;
+use std::sync::{Arc, Mutex, RwLock, RwLockWriteGuard};
 use std::thread;
 use std::time::Duration;
-use crossterm::style::Color;
+use crossterm::style::{Color, ContentStyle, StyledContent, Stylize};
 
 pub type TerminalMenu = Arc<RwLock<TerminalMenuStruct>>;
 
@@ -24,7 +41,13 @@ enum TMIKind {
 pub struct TerminalMenuItem {
     name: String,
     kind: TMIKind,
-    color: crossterm::style::Color,
+    style: ContentStyle,
+    spans: Vec<utils::Span>,
+    description: String,
+    /// Selects the item, or presses it if it is a button. Always lowercase.
+    hotkey: Option<char>,
+    /// Position of the character of the name drawn as the hotkey.
+    mnemonic: Option<usize>,
 }
 
 
@@ -44,7 +67,11 @@ pub fn label<T: Into<String>>(text: T) -> TerminalMenuItem {
     TerminalMenuItem {
         name: text.into(),
         kind: TMIKind::Label,
-        color: Color::Reset
+        style: ContentStyle::new(),
+        spans: Vec::new(),
+        description: String::new(),
+        hotkey: None,
+        mnemonic: None
     }
 }
 
@@ -64,7 +91,11 @@ pub fn button<T: Into<String>>(name: T) -> TerminalMenuItem {
     TerminalMenuItem {
         name: name.into(),
         kind: TMIKind::Button,
-        color: Color::Reset
+        style: ContentStyle::new(),
+        spans: Vec::new(),
+        description: String::new(),
+        hotkey: None,
+        mnemonic: None
     }
 }
 
@@ -84,7 +115,11 @@ pub fn back_button<T: Into<String>>(name: T) -> TerminalMenuItem {
     TerminalMenuItem {
         name: name.into(),
         kind: TMIKind::BackButton,
-        color: Color::Reset
+        style: ContentStyle::new(),
+        spans: Vec::new(),
+        description: String::new(),
+        hotkey: None,
+        mnemonic: None
     }
 }
 
@@ -114,7 +149,11 @@ pub fn scroll<T: Into<String>, T2: IntoIterator>(name: T, values: T2) -> Termina
             values,
             selected: 0
         },
-        color: Color::Reset
+        style: ContentStyle::new(),
+        spans: Vec::new(),
+        description: String::new(),
+        hotkey: None,
+        mnemonic: None
     }
 }
 
@@ -144,7 +183,11 @@ pub fn list<T: Into<String>, T2: IntoIterator>(name: T, values: T2) -> TerminalM
             values,
             selected: 0
         },
-        color: Color::Reset
+        style: ContentStyle::new(),
+        spans: Vec::new(),
+        description: String::new(),
+        hotkey: None,
+        mnemonic: None
     }
 }
 
@@ -163,7 +206,11 @@ pub fn string<T: Into<String>, T2: Into<String>>(name: T, default: T2, allow_emp
     TerminalMenuItem {
         name: name.into(),
         kind: TMIKind::String { value: default.into(), allow_empty },
-        color: Color::Reset,
+        style: ContentStyle::new(),
+        spans: Vec::new(),
+        description: String::new(),
+        hotkey: None,
+        mnemonic: None
     }
 }
 
@@ -194,7 +241,11 @@ pub fn numeric<T: Into<String>>(name: T, default: f64, step: Option<f64>, min: O
             min,
             max
         },
-        color: Color::Reset
+        style: ContentStyle::new(),
+        spans: Vec::new(),
+        description: String::new(),
+        hotkey: None,
+        mnemonic: None
     }
 }
 
@@ -222,7 +273,11 @@ pub fn submenu<T: Into<String> + Clone>(name: T, items: Vec<TerminalMenuItem>) -
     TerminalMenuItem {
         name: name.into(),
         kind: TMIKind::Submenu(menu),
-        color: Color::Reset
+        style: ContentStyle::new(),
+        spans: Vec::new(),
+        description: String::new(),
+        hotkey: None,
+        mnemonic: None
     }
 }
 
@@ -244,21 +299,236 @@ impl TerminalMenuItem {
     /// ]);
     /// ```
     pub fn colorize(mut self, color: Color) -> Self {
-        self.color = color;
+        self.style = self.style.with(color);
+        self
+    }
+
+    /// Show the name as pieces of text with styles of their own, such as colors, bold or dim.
+    /// The plain name is still used to find the item.
+    /// The hotkey and the characters matching the filter are marked only if the text of the spans starts with the name.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, label, button};
+    /// use crossterm::style::Stylize;
+    /// let menu = menu(vec![
+    ///     label("").styled(vec!["Choose ".bold(), "carefully".red().italic()]),
+    ///     button("Install").styled(vec!["Install".stylize(), " (recommended)".dim()]),
+    ///     button("Skip")
+    /// ]);
+    /// ```
+    pub fn styled<T: Display, I: IntoIterator<Item = StyledContent<T>>>(mut self, spans: I) -> Self {
+        self.spans = spans.into_iter()
+            .map(|a| utils::span(a.content().to_string(), *a.style()))
+            .collect();
+        self
+    }
+
+    /// Set a longer text about the item.
+    /// It is shown below the items when the item is selected, wrapped to the width of the menu.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, scroll, numeric};
+    /// let menu = menu(vec![
+    ///     scroll("Log level", vec!["warn", "info", "debug"])
+    ///         .describe("How much is written to the log file. Debug logs can get large quickly."),
+    ///     numeric("Workers", 4.0, Some(1.0), Some(1.0), Some(64.0))
+    ///         .describe("Number of threads handling requests.")
+    /// ]);
+    /// ```
+    pub fn describe<T: Into<String>>(mut self, description: T) -> Self {
+        self.description = description.into();
         self
     }
 
+    /// Set a key that selects the item, or presses it if it is a button.
+    /// The first character of the name that is the key is underlined.
+    /// Labels can't be selected, so their hotkeys do nothing.
+    /// Hotkeys can't be keys of the keymap, see [TerminalMenuStruct::check_hotkeys].
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, button, mut_menu, KeyMap};
+    /// let menu = menu(vec![
+    ///     button("Open").hotkey('o'),
+    ///     button("Export").hotkey('x')
+    /// ]);
+    /// // the default keys include wasd and q
+    /// mut_menu(&menu).set_keymap(KeyMap::arrows()).unwrap();
+    /// assert_eq!(mut_menu(&menu).check_hotkeys(), Ok(()));
+    /// ```
+    pub fn hotkey(mut self, key: char) -> Self {
+        let key = key.to_lowercase().next().unwrap_or(key);
+        self.hotkey = Some(key);
+        self.mnemonic = self.name.chars().position(|a| a.to_lowercase().eq(key.to_lowercase()));
+        self
+    }
+
+    /// Take the hotkey from the name, where it is marked with & such as "&Save" or "Save &as".
+    /// The & is removed from the name and && is a plain &.
+    /// Names are used as they are unless this is called, so "R&D" stays "R&D".
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, button, mut_menu, KeyMap};
+    /// let menu = menu(vec![
+    ///     button("&Open").mnemonic(),
+    ///     button("Save &as").mnemonic(),
+    ///     button("R&D")
+    /// ]);
+    /// mut_menu(&menu).set_keymap(KeyMap::arrows()).unwrap();
+    /// assert_eq!(mut_menu(&menu).selected_item_name(), "Open");
+    /// ```
+    pub fn mnemonic(mut self) -> Self {
+        let (name, hotkey) = utils::parse_mnemonic(std::mem::take(&mut self.name));
+        if let TMIKind::Submenu(submenu) = &self.kind {
+            submenu.write().unwrap().name = Some(name.clone());
+        }
+        self.name = name;
+        if let Some((key, position)) = hotkey {
+            self.hotkey = Some(key);
+            self.mnemonic = Some(position);
+        }
+        self
+    }
+
+}
+
+/// Lines drawn around a menu, with the name of the menu as the title.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Border {
+    /// No border and no title.
+    None,
+    /// ┌─┐
+    Single,
+    /// ╔═╗
+    Double,
+    /// ╭─╮
+    Rounded,
+    /// +-+
+    Ascii,
 }
 
+pub(crate) struct BorderGlyphs {
+    top_left: char,
+    top_right: char,
+    bottom_left: char,
+    bottom_right: char,
+    horizontal: char,
+    vertical: char,
+}
+
+impl Border {
+    pub(crate) fn glyphs(self) -> Option<BorderGlyphs> {
+        let [top_left, top_right, bottom_left, bottom_right, horizontal, vertical] = match self {
+            Border::None    => return None,
+            Border::Single  => ['┌', '┐', '└', '┘', '─', '│'],
+            Border::Double  => ['╔', '╗', '╚', '╝', '═', '║'],
+            Border::Rounded => ['╭', '╮', '╰', '╯', '─', '│'],
+            Border::Ascii   => ['+', '+', '+', '+', '-', '|'],
+        };
+        Some(BorderGlyphs { top_left, top_right, bottom_left, bottom_right, horizontal, vertical })
+    }
+}
+
+/// Where the items were laid out on the last draw.
+#[derive(Default)]
+pub(crate) struct View {
+    /// The first visible item, or the first visible row of the grid.
+    top: usize,
+    /// The items on each row of the grid, with the widths of their columns.
+    grid: Vec<Vec<(usize, usize)>>,
+    /// The text of the status line.
+    status: String,
+    /// The item whose preview is drawn, None while the preview is not ready.
+    preview: Option<usize>,
+    /// Where the value of the selected item starts, as a row and a column of the rows inside the border.
+    anchor: (usize, usize),
+    /// The first visible value in the popup.
+    popup_top: usize,
+    /// The items of the parent menu, shown left of the items in miller columns.
+    parent_column: Option<Column>,
+    /// The visible items.
+    range: std::ops::Range<usize>,
+    /// The parts of the screen that can be clicked.
+    hits: Vec<Hit>,
+    /// The item clicked last and when, for noticing double clicks.
+    last_click: Option<(usize, std::time::Instant)>,
+}
+
+/// A part of the screen that selects an item or a value when clicked.
+#[derive(Clone)]
+pub(crate) struct Hit {
+    row: usize,
+    columns: std::ops::Range<usize>,
+    item: usize,
+    /// The value of a list item or a value in the popup.
+    value: Option<usize>,
+    popup: bool,
+}
+
+/// The names of the items of a menu, drawn as a column next to another menu.
+#[derive(Clone)]
+pub(crate) struct Column {
+    names: Vec<Vec<utils::Span>>,
+    selected: usize,
+}
+
+/// What is currently drawn on the terminal, one string per row.
 pub(crate) enum PrintState {
     None,
-    Big
+    Big(Vec<String>),
+    Small(Vec<String>),
+}
+
+/// How a menu is drawn on the terminal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrintMode {
+    /// Take over the whole terminal using the alternate screen.
+    FullScreen,
+    /// Draw the menu under the cursor and erase it on exit.
+    /// With summary the selected item is left on screen as a single line.
+    Inline { summary: bool },
+}
+
+/// What to do with items too wide for the terminal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Overflow {
+    /// Cut the item off with an ellipsis.
+    Truncate,
+    /// Continue the item on the following rows.
+    Wrap,
+}
+
+/// Where the values of scroll, list, string and numeric items start.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueAlignment {
+    /// A few columns after the longest name.
+    AfterNames,
+    /// At the column, counted from the start of the names.
+    /// Values of items with longer names start one space after the name.
+    Column(usize),
+    /// At the right edge of the menu.
+    Right,
+}
+
+/// Where the preview pane is drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PreviewPosition {
+    /// Right of the items.
+    Right,
+    /// Below the items.
+    Bottom,
 }
 
 pub struct TerminalMenuStruct {
     name: Option<String>,
     pub items: Vec<TerminalMenuItem>,
     selected: usize,
+    /// The value selected in the popup of the selected item, if the popup is open.
+    popup: Option<usize>,
+    /// The typed filter of the values in the popup.
+    popup_query: String,
+    /// The typed filter, if the items are being filtered.
+    query: Option<String>,
+    view: View,
     active: bool,
     exited: bool,
 
@@ -266,6 +536,30 @@ pub struct TerminalMenuStruct {
     exit: Option<String>,
     canceled: bool,
     printed: PrintState,
+    print_mode: Option<PrintMode>,
+    overflow: Option<Overflow>,
+    theme: Option<Theme>,
+    color_support: Option<ColorSupport>,
+    border: Option<Border>,
+    padding: Option<usize>,
+    margin: Option<usize>,
+    centered: Option<(bool, bool)>,
+    max_width: Option<usize>,
+    value_alignment: Option<ValueAlignment>,
+    scrolloff: Option<usize>,
+    scrollbar: Option<bool>,
+    position_counter: Option<bool>,
+    grid: bool,
+    miller_columns: Option<bool>,
+    keymap: Option<KeyMap>,
+    mouse: Option<bool>,
+    filter: Option<bool>,
+    header: Vec<String>,
+    footer: Vec<String>,
+    status: Option<Status>,
+    help_bar: Option<bool>,
+    preview: Option<(PreviewPosition, Preview)>,
+    backend: Option<SharedBackend>,
 }
 impl TerminalMenuStruct {
 
@@ -384,7 +678,7 @@ impl TerminalMenuStruct {
     /// run(&my_menu);
     /// println!("{}", mut_menu(&my_menu).get_submenu("sub").selection_value("item"));
     /// ```
-    pub fn get_submenu(&mut self, name: &str) -> RwLockWriteGuard<TerminalMenuStruct> {
+    pub fn get_submenu(&mut self, name: &str) -> RwLockWriteGuard<'_, TerminalMenuStruct> {
         for item in &self.items {
             if item.name == name {
                 if let TMIKind::Submenu(submenu) = &item.kind {
@@ -403,7 +697,7 @@ impl TerminalMenuStruct {
         }
     }
 
-    /// Returns true if menu was exited with 'q' or esc
+    /// Returns true if menu was exited with a key bound to [Action::Cancel], such as esc
     /// # Example
     /// ```
     /// use terminal_menu::{menu, button, run, mut_menu};
@@ -412,13 +706,367 @@ impl TerminalMenuStruct {
     /// ]);
     /// run(&menu);
     ///
-    /// // true if esc, false if button
+    /// // true if canceled, false if button
     /// println!("{}", mut_menu(&menu).canceled());
     /// ```
     pub fn canceled(&self) -> bool {
         self.canceled
     }
 
+    /// Set where the menu is drawn. Defaults to stdout.
+    /// Use stderr or the controlling terminal (see tty) to keep stdout free for piping.
+    /// Submenus without a backend of their own are drawn on their parents backend.
+    /// The menu keeps the backend instead of run and activate taking it, so submenus and later runs use it too.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, button, mut_menu};
+    /// let my_menu = menu(vec![
+    ///     button("Alice"),
+    ///     button("Bob")
+    /// ]);
+    /// mut_menu(&my_menu).set_backend(std::io::stderr());
+    /// ```
+    pub fn set_backend<B: Backend + 'static>(&mut self, backend: B) {
+        self.backend = Some(Arc::new(Mutex::new(Box::new(backend))));
+    }
+
+    /// Set how the menu is drawn. Defaults to full screen.
+    /// Submenus without a print mode of their own use their parents print mode.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, button, mut_menu, PrintMode};
+    /// let my_menu = menu(vec![
+    ///     button("Yes"),
+    ///     button("No")
+    /// ]);
+    /// mut_menu(&my_menu).set_print_mode(PrintMode::Inline { summary: true });
+    /// ```
+    pub fn set_print_mode(&mut self, print_mode: PrintMode) {
+        self.print_mode = Some(print_mode);
+    }
+
+    /// Set what to do with items too wide for the terminal. Defaults to truncating them.
+    /// Submenus without an overflow of their own use their parents overflow.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, string, mut_menu, Overflow};
+    /// let my_menu = menu(vec![
+    ///     string("Notes", "a very long default value that may not fit on a narrow terminal", true)
+    /// ]);
+    /// mut_menu(&my_menu).set_overflow(Overflow::Wrap);
+    /// ```
+    pub fn set_overflow(&mut self, overflow: Overflow) {
+        self.overflow = Some(overflow);
+    }
+
+    /// Set the glyphs and styles the menu is drawn with.
+    /// Submenus without a theme of their own use their parents theme.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, button, mut_menu, Theme};
+    /// use crossterm::style::{ContentStyle, Stylize};
+    /// let my_menu = menu(vec![
+    ///     button("Alice"),
+    ///     button("Bob")
+    /// ]);
+    /// mut_menu(&my_menu).set_theme(Theme {
+    ///     selected: ContentStyle::new().reverse(),
+    ///     ..Theme::default()
+    /// });
+    /// ```
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = Some(theme);
+    }
+
+    /// Set the colors the terminal can show instead of detecting them from the environment.
+    /// See ColorSupport.
+    pub fn set_color_support(&mut self, color_support: ColorSupport) {
+        self.color_support = Some(color_support);
+    }
+
+    /// Set the border drawn around the menu. Defaults to no border.
+    /// Submenus show their name in the border and use their parents border unless they have their own.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, submenu, back_button, mut_menu, Border};
+    /// let my_menu = menu(vec![
+    ///     submenu("Settings", vec![
+    ///         back_button("Back")
+    ///     ]),
+    ///     back_button("Exit")
+    /// ]);
+    /// mut_menu(&my_menu).set_border(Border::Rounded);
+    /// ```
+    pub fn set_border(&mut self, border: Border) {
+        self.border = Some(border);
+    }
+
+    /// Set the amount of empty rows and columns between the border and the items.
+    /// Submenus without padding of their own use their parents padding.
+    pub fn set_padding(&mut self, padding: usize) {
+        self.padding = Some(padding);
+    }
+
+    /// Set the amount of empty rows and columns around the border.
+    /// Submenus without a margin of their own use their parents margin.
+    pub fn set_margin(&mut self, margin: usize) {
+        self.margin = Some(margin);
+    }
+
+    /// Center the menu horizontally, vertically or both.
+    /// A horizontally centered menu is as wide as its widest row.
+    /// Inline menus are never centered vertically.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, label, button, mut_menu, Border};
+    /// let my_menu = menu(vec![
+    ///     label("Delete 3 files?"),
+    ///     button("Yes"),
+    ///     button("No")
+    /// ]);
+    /// mut_menu(&my_menu).set_border(Border::Rounded);
+    /// mut_menu(&my_menu).set_centered(true, true);
+    /// ```
+    pub fn set_centered(&mut self, horizontally: bool, vertically: bool) {
+        self.centered = Some((horizontally, vertically));
+    }
+
+    /// Set the maximum width of the contents of the menu, without the border, padding and margin.
+    /// Submenus without a maximum width of their own use their parents.
+    pub fn set_max_width(&mut self, max_width: usize) {
+        self.max_width = Some(max_width);
+    }
+
+    /// Set where the values of items start. See ValueAlignment.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, scroll, numeric, mut_menu, ValueAlignment};
+    /// let my_menu = menu(vec![
+    ///     scroll("Size", vec!["small", "medium", "large"]),
+    ///     numeric("Count", 1.0, Some(1.0), Some(1.0), None)
+    /// ]);
+    /// mut_menu(&my_menu).set_max_width(40);
+    /// mut_menu(&my_menu).set_value_alignment(ValueAlignment::Right);
+    /// ```
+    pub fn set_value_alignment(&mut self, value_alignment: ValueAlignment) {
+        self.value_alignment = Some(value_alignment);
+    }
+
+    /// Set how many items to keep visible above and below the selected item when scrolling.
+    /// The items only scroll when the selected item gets closer to the edge than this. Defaults to 3.
+    /// A large value keeps the selected item centered.
+    /// Submenus use their parents scrolloff unless they have their own.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, button, mut_menu};
+    /// let my_menu = menu((1..100).map(|n| button(n.to_string())).collect());
+    /// mut_menu(&my_menu).set_scrolloff(5);
+    /// ```
+    pub fn set_scrolloff(&mut self, scrolloff: usize) {
+        self.scrolloff = Some(scrolloff);
+    }
+
+    /// Show a scrollbar next to the items when they don't all fit on the screen.
+    /// Submenus use their parents setting unless they have their own.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, button, mut_menu};
+    /// let my_menu = menu((1..=340).map(|n| button(n.to_string())).collect());
+    /// mut_menu(&my_menu).set_scrollbar(true);
+    /// ```
+    pub fn set_scrollbar(&mut self, scrollbar: bool) {
+        self.scrollbar = Some(scrollbar);
+    }
+
+    /// Show the position of the selected item below the items, such as "12/340".
+    /// Submenus use their parents setting unless they have their own.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, button, mut_menu};
+    /// let my_menu = menu((1..=340).map(|n| button(n.to_string())).collect());
+    /// mut_menu(&my_menu).set_position_counter(true);
+    /// ```
+    pub fn set_position_counter(&mut self, position_counter: bool) {
+        self.position_counter = Some(position_counter);
+    }
+
+    /// Flow the items into as many columns as fit the terminal instead of a single column.
+    /// Labels get rows of their own. Left and right move between the columns
+    /// unless the selected item has a value to change.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, label, button, mut_menu};
+    /// let mut items = vec![label("pick a region:")];
+    /// items.extend((1..=60).map(|n| button(format!("region-{}", n))));
+    /// let my_menu = menu(items);
+    /// mut_menu(&my_menu).set_grid(true);
+    /// ```
+    pub fn set_grid(&mut self, grid: bool) {
+        self.grid = grid;
+    }
+
+    /// Show the parent menu left of the items and the items of the selected submenu right of them.
+    /// Right opens the selected submenu and left goes back to the parent menu.
+    /// Submenus use miller columns too unless they are set otherwise.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, submenu, button, mut_menu};
+    /// let my_menu = menu(vec![
+    ///     submenu("Fruits", vec![
+    ///         submenu("Apples", vec![button("Fuji"), button("Gala")]),
+    ///         button("Banana")
+    ///     ]),
+    ///     submenu("Vegetables", vec![button("Carrot"), button("Potato")])
+    /// ]);
+    /// mut_menu(&my_menu).set_miller_columns(true);
+    /// ```
+    pub fn set_miller_columns(&mut self, miller_columns: bool) {
+        self.miller_columns = Some(miller_columns);
+    }
+
+    /// Set the keys of the menu. See KeyMap.
+    /// Submenus without a keymap of their own use their parents keymap.
+    /// Returns an error and keeps the old keys if a hotkey of the items is one of the keys,
+    /// see [TerminalMenuStruct::check_hotkeys].
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, button, mut_menu, KeyMap};
+    /// let my_menu = menu(vec![
+    ///     button("Quit"),
+    ///     button("Quarantine")
+    /// ]);
+    /// // q doesn't cancel with arrow keys only
+    /// mut_menu(&my_menu).set_keymap(KeyMap::arrows()).unwrap();
+    /// ```
+    pub fn set_keymap(&mut self, keymap: KeyMap) -> Result<(), HotkeyError> {
+        let old = self.keymap.replace(keymap);
+        if let Err(error) = self.check_hotkeys() {
+            self.keymap = old;
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    /// Check that the hotkeys of the items do what they should.
+    /// Hotkeys can't be keys of the keymap and items of the same menu can't share them.
+    /// Submenus are checked with the keymap they inherit.
+    /// [try_menu] and [TerminalMenuStruct::set_keymap] check the hotkeys already,
+    /// and running a menu that fails the check panics in debug builds.
+    /// In release builds keys of the keymap keep doing their action, and only the first of the items sharing a key gets it.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, button, mut_menu, HotkeyError, Action};
+    /// let menu = menu(vec![
+    ///     button("Save").hotkey('s'),
+    ///     button("Load").hotkey('l')
+    /// ]);
+    /// // s moves down with the default keys
+    /// assert_eq!(mut_menu(&menu).check_hotkeys(), Err(HotkeyError::Bound {
+    ///     item: "Save".to_owned(),
+    ///     key: 's',
+    ///     action: Action::MoveDown
+    /// }));
+    /// ```
+    pub fn check_hotkeys(&self) -> Result<(), HotkeyError> {
+        fancy_menu::check_hotkeys(self, None)
+    }
+
+    /// Use the mouse in full screen menus.
+    /// Clicks select items and values of list items, double clicks press items and the wheel scrolls.
+    /// Submenus use the mouse too unless they are set otherwise.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, button, list, mut_menu};
+    /// let my_menu = menu(vec![
+    ///     list("Size", vec!["small", "medium", "large"]),
+    ///     button("Done")
+    /// ]);
+    /// mut_menu(&my_menu).set_mouse(true);
+    /// ```
+    pub fn set_mouse(&mut self, mouse: bool) {
+        self.mouse = Some(mouse);
+    }
+
+    /// Filter the items by typing.
+    /// Typing a key that does nothing else, or a key of [Action::Filter] such as /, shows only the items
+    /// whose names match the typed text, best matches first with the matched characters highlighted (see [fuzzy_match]).
+    /// Enter presses the selected match and a key of [Action::Cancel] such as esc shows all the items again.
+    /// Submenus can be filtered too unless they are set otherwise.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, button, mut_menu, KeyMap};
+    /// let my_menu = menu((1..500).map(|n| button(n.to_string())).collect());
+    /// mut_menu(&my_menu).set_filter(true);
+    /// // free the letters for typing
+    /// mut_menu(&my_menu).set_keymap(KeyMap::arrows()).unwrap();
+    /// ```
+    pub fn set_filter(&mut self, filter: bool) {
+        self.filter = Some(filter);
+    }
+
+    /// Set lines of text shown above the items.
+    /// Unlike labels they stay in place when the items scroll.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, button, mut_menu};
+    /// let my_menu = menu((1..100).map(|n| button(n.to_string())).collect());
+    /// mut_menu(&my_menu).set_header(vec!["Pick a number", "---"]);
+    /// ```
+    pub fn set_header<T: IntoIterator>(&mut self, lines: T) where T::Item: Into<String> {
+        self.header = lines.into_iter().map(|a| a.into()).collect();
+    }
+
+    /// Set lines of text shown below the items.
+    /// Unlike labels they stay in place when the items scroll.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, button, mut_menu};
+    /// let my_menu = menu((1..100).map(|n| button(n.to_string())).collect());
+    /// mut_menu(&my_menu).set_footer(vec!["enter to pick, q to quit"]);
+    /// ```
+    pub fn set_footer<T: IntoIterator>(&mut self, lines: T) where T::Item: Into<String> {
+        self.footer = lines.into_iter().map(|a| a.into()).collect();
+    }
+
+    /// Set the status line shown below the footer. See Status.
+    /// Submenus show their parents status line unless they have their own.
+    pub fn set_status(&mut self, status: Status) {
+        self.status = Some(status);
+    }
+
+    /// Show the keys that can be used with the selected item at the bottom.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, button, scroll, mut_menu};
+    /// let my_menu = menu(vec![
+    ///     scroll("Size", vec!["small", "medium", "large"]),
+    ///     button("Done")
+    /// ]);
+    /// // shows "↑/↓ move  ←/→ change  Enter pick  Esc cancel" when Size is selected
+    /// mut_menu(&my_menu).set_help_bar(true);
+    /// ```
+    pub fn set_help_bar(&mut self, help_bar: bool) {
+        self.help_bar = Some(help_bar);
+    }
+
+    /// Show a preview of the selected item next to the items.
+    /// The function gets the name of the item and returns the text of the preview.
+    /// It runs on a separate thread, so the menu can be used while a slow preview is made.
+    /// # Example
+    /// ```
+    /// use terminal_menu::{menu, button, mut_menu, PreviewPosition};
+    /// let my_menu = menu(vec![
+    ///     button("Cargo.toml"),
+    ///     button("README.md")
+    /// ]);
+    /// mut_menu(&my_menu).set_preview(PreviewPosition::Right, |name| {
+    ///     std::fs::read_to_string(name).unwrap_or_else(|e| e.to_string())
+    /// });
+    /// ```
+    pub fn set_preview<F: Fn(&str) -> String + Send + Sync + 'static>(&mut self, position: PreviewPosition, preview: F) {
+        self.preview = Some((position, Preview::new(preview)));
+    }
+
 }
 
 /// Create a terminal-menu. See the examples for more.
@@ -445,6 +1093,10 @@ pub fn menu(items: Vec<TerminalMenuItem>) -> TerminalMenu {
                 name: None,
                 items,
                 selected: i,
+                popup: None,
+                popup_query: String::new(),
+                query: None,
+                view: View::default(),
                 active: false,
                 exited: true,
 
@@ -452,12 +1104,57 @@ pub fn menu(items: Vec<TerminalMenuItem>) -> TerminalMenu {
                 exit: None,
                 canceled: false,
                 printed: PrintState::None,
+                print_mode: None,
+                overflow: None,
+                theme: None,
+                color_support: None,
+                border: None,
+                padding: None,
+                margin: None,
+                centered: None,
+                max_width: None,
+                value_alignment: None,
+                scrolloff: None,
+                scrollbar: None,
+                position_counter: None,
+                grid: false,
+                miller_columns: None,
+                keymap: None,
+                mouse: None,
+                filter: None,
+                header: Vec::new(),
+                footer: Vec::new(),
+                status: None,
+                help_bar: None,
+                preview: None,
+                backend: None,
             }))
         }
     }
     panic!("no selectable items");
 }
 
+/// Create a terminal-menu like [menu], checking the hotkeys of the items against the default keys and each other.
+/// To use hotkeys that are default keys, create the menu with [menu] and set a keymap without them.
+/// See [TerminalMenuStruct::check_hotkeys].
+/// # Example
+/// ```
+/// use terminal_menu::{try_menu, button, HotkeyError};
+/// let result = try_menu(vec![
+///     button("Open").hotkey('o'),
+///     button("Options").hotkey('o')
+/// ]);
+/// assert_eq!(result.err(), Some(HotkeyError::Duplicate {
+///     items: ("Open".to_owned(), "Options".to_owned()),
+///     key: 'o'
+/// }));
+/// ```
+pub fn try_menu(items: Vec<TerminalMenuItem>) -> Result<TerminalMenu, HotkeyError> {
+    let menu = menu(items);
+    menu.read().unwrap().check_hotkeys()?;
+    Ok(menu)
+}
+
 /// Returns true if the menu has exited.
 pub fn has_exited(menu: &TerminalMenu) -> bool {
     menu.read().unwrap().exited
@@ -484,7 +1181,7 @@ pub fn has_exited(menu: &TerminalMenu) -> bool {
 /// run(&my_menu);
 ///
 /// ```
-pub fn mut_menu(menu: &TerminalMenu) -> RwLockWriteGuard<TerminalMenuStruct> {
+pub fn mut_menu(menu: &TerminalMenu) -> RwLockWriteGuard<'_, TerminalMenuStruct> {
     if !has_exited(menu) {
         panic!("Cannot call mutable_instance if has_exited() is not true");
     }
@@ -0,0 +1,137 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, Once};
+use std::thread;
+
+/// Name of the worker threads, whose panics are shown in the preview pane instead of on the terminal.
+const WORKER: &str = "terminal-menu preview";
+
+static QUIET_WORKERS: Once = Once::new();
+
+/// Makes the text of the preview pane for the name of an item.
+type Callback = dyn Fn(&str) -> String + Send + Sync;
+
+/// The preview callback of a menu and its latest result.
+/// The callback runs on a thread of its own so a slow preview doesn't block the input.
+#[derive(Clone)]
+pub(crate) struct Preview {
+    callback: Arc<Callback>,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    /// The index and name of the item to make a preview of next.
+    wanted: Option<(usize, String)>,
+    /// The index of the item of the latest preview, and the preview.
+    done: Option<(usize, String)>,
+    /// True while the worker thread is alive.
+    running: bool,
+}
+
+impl Preview {
+
+    pub(crate) fn new<F: Fn(&str) -> String + Send + Sync + 'static>(callback: F) -> Self {
+        Preview {
+            callback: Arc::new(callback),
+            state: Arc::new(Mutex::new(State::default())),
+        }
+    }
+
+    /// Make a preview of the item unless there is one already.
+    /// Only the latest request is kept while the callback is busy.
+    pub(crate) fn request(&self, index: usize, name: &str) {
+        let mut state = self.state.lock().unwrap();
+        if state.done.as_ref().is_some_and(|a| a.0 == index) {
+            state.wanted = None;
+            return;
+        }
+        state.wanted = Some((index, name.to_owned()));
+        if !state.running {
+            state.running = true;
+            let preview = self.clone();
+            QUIET_WORKERS.call_once(|| {
+                // the default hook would print over the menu, which is not redrawn there
+                let hook = panic::take_hook();
+                panic::set_hook(Box::new(move |info| {
+                    if thread::current().name() != Some(WORKER) {
+                        hook(info);
+                    }
+                }));
+            });
+            thread::Builder::new()
+                .name(WORKER.to_owned())
+                .spawn(move || preview.work())
+                .unwrap();
+        }
+    }
+
+    fn work(&self) {
+        loop {
+            let (index, name) = {
+                let mut state = self.state.lock().unwrap();
+                match state.wanted.take() {
+                    Some(wanted) => wanted,
+                    None => {
+                        state.running = false;
+                        return;
+                    }
+                }
+            };
+            // a panicking callback must not take the worker down with it
+            let text = match panic::catch_unwind(AssertUnwindSafe(|| (self.callback)(&name))) {
+                Ok(text) => text,
+                Err(payload) => match payload.downcast_ref::<&str>() {
+                    Some(message) => format!("preview failed: {}", message),
+                    None => match payload.downcast_ref::<String>() {
+                        Some(message) => format!("preview failed: {}", message),
+                        None => "preview failed".to_owned()
+                    }
+                }
+            };
+            self.state.lock().unwrap().done = Some((index, text));
+        }
+    }
+
+    /// The preview of the item, if it is ready.
+    pub(crate) fn get(&self, index: usize) -> Option<String> {
+        match &self.state.lock().unwrap().done {
+            Some((done, text)) if *done == index => Some(text.clone()),
+            _ => None
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Preview;
+    use std::time::{Duration, Instant};
+
+    fn wait(preview: &Preview, index: usize) -> Option<String> {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(5) {
+            if let Some(text) = preview.get(index) {
+                return Some(text);
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        None
+    }
+
+    #[test]
+    fn panicking_callback_is_reported_and_worker_restarts() {
+        let preview = Preview::new(|name| if name == "bad" { panic!("boom") } else { name.to_uppercase() });
+        preview.request(0, "bad");
+        assert_eq!(wait(&preview, 0).as_deref(), Some("preview failed: boom"));
+        preview.request(1, "good");
+        assert_eq!(wait(&preview, 1).as_deref(), Some("GOOD"));
+    }
+
+    #[test]
+    fn previews_are_kept_by_index() {
+        let preview = Preview::new(|name| name.to_owned());
+        preview.request(0, "same");
+        assert!(wait(&preview, 0).is_some());
+        assert_eq!(preview.get(1), None);
+    }
+}
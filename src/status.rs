@@ -0,0 +1,42 @@
+use std::sync::{Arc, Mutex};
+
+/// A line of text below the items that can be changed while the menu is active.
+/// Clones share the same text, keep one to update the menu from anywhere.
+/// # Example
+/// ```
+/// use terminal_menu::{menu, button, mut_menu, Status};
+/// let my_menu = menu(vec![
+///     button("Save"),
+///     button("Quit")
+/// ]);
+/// let status = Status::new("no unsaved changes");
+/// mut_menu(&my_menu).set_status(status.clone());
+///
+/// // later, even while the menu is active
+/// status.set("3 unsaved changes");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Status {
+    text: Arc<Mutex<String>>,
+}
+
+impl Status {
+
+    /// Make a status line with the text.
+    pub fn new<T: Into<String>>(text: T) -> Self {
+        Status {
+            text: Arc::new(Mutex::new(text.into())),
+        }
+    }
+
+    /// Change the text. Active menus show the new text shortly.
+    pub fn set<T: Into<String>>(&self, text: T) {
+        *self.text.lock().unwrap() = text.into();
+    }
+
+    /// Returns the current text.
+    pub fn get(&self) -> String {
+        self.text.lock().unwrap().clone()
+    }
+
+}
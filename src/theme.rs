@@ -0,0 +1,74 @@
+use crossterm::style::{Color, ContentStyle, Stylize};
+
+/// The glyphs and styles a menu is drawn with.
+/// Styles can set the foreground, the background and attributes such as bold or reverse.
+/// # Example
+/// ```
+/// use terminal_menu::{menu, button, mut_menu, Theme};
+/// use crossterm::style::{Color, ContentStyle, Stylize};
+/// let my_menu = menu(vec![
+///     button("Alice"),
+///     button("Bob")
+/// ]);
+/// mut_menu(&my_menu).set_theme(Theme {
+///     cursor: "→ ".to_owned(),
+///     selected: ContentStyle::new().with(Color::Black).on(Color::Yellow).bold(),
+///     ..Theme::default()
+/// });
+/// ```
+#[derive(Clone, Debug)]
+pub struct Theme {
+    /// Drawn in front of the selected item.
+    pub cursor: String,
+    /// The selected item.
+    pub selected: ContentStyle,
+    /// Names of items that can be selected.
+    pub name: ContentStyle,
+    /// Values of scroll, list, string and numeric items.
+    pub value: ContentStyle,
+    /// The current value when picking a value of a scroll or list item.
+    pub current_value: ContentStyle,
+    /// Text of labels, the items made with label() that can't be selected.
+    pub label_text: ContentStyle,
+    /// The character of the name that is the hotkey of the item.
+    pub mnemonic: ContentStyle,
+    /// Characters of names and values that match the typed filter.
+    pub matched: ContentStyle,
+    /// Titles and other text around the items.
+    pub header: ContentStyle,
+    /// Lines of the border around the menu, and the scrollbar.
+    pub border: ContentStyle,
+    /// Shown above the items when there are more items above them.
+    pub more_above: String,
+    /// Shown below the items when there are more items below them.
+    pub more_below: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            cursor: "> ".to_owned(),
+            selected: ContentStyle::new().with(Color::Cyan),
+            name: ContentStyle::new(),
+            value: ContentStyle::new(),
+            current_value: ContentStyle::new().with(Color::Green),
+            label_text: ContentStyle::new(),
+            mnemonic: ContentStyle::new().underlined(),
+            matched: ContentStyle::new().with(Color::Yellow).bold(),
+            header: ContentStyle::new(),
+            border: ContentStyle::new(),
+            more_above: "...".to_owned(),
+            more_below: "...".to_owned(),
+        }
+    }
+}
+
+/// The base style with the colors and attributes of the other style on top.
+pub(crate) fn merge(base: ContentStyle, over: ContentStyle) -> ContentStyle {
+    ContentStyle {
+        foreground_color: over.foreground_color.or(base.foreground_color),
+        background_color: over.background_color.or(base.background_color),
+        underline_color: over.underline_color.or(base.underline_color),
+        attributes: base.attributes | over.attributes,
+    }
+}
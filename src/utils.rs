@@ -1,7 +1,11 @@
-use std::io::stdout;
+use std::io::Write;
 use std::time::Duration;
 use crossterm::*;
 use lazy_static::lazy_static;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+use crate::ColorSupport;
+use crate::theme::merge;
 
 const MAX_FLOAT_PRINTING_PRECISION: usize = 10;
 
@@ -9,17 +13,200 @@ lazy_static! {
     pub static ref INTERVAL: Duration = Duration::from_millis(100);
 }
 
-pub fn term_height() -> usize {
-    terminal::size().unwrap().1 as usize
+/// Number of terminal columns the string takes up.
+/// Grapheme clusters such as emoji sequences are at most two columns wide.
+pub fn width(s: &str) -> usize {
+    s.graphemes(true).map(|g| g.width().min(2)).sum()
 }
-pub fn _term_width() -> usize {
-    terminal::size().unwrap().0 as usize
+
+/// A piece of text with a single style.
+pub type Span = style::StyledContent<String>;
+
+pub fn span<T: Into<String>>(text: T, style: style::ContentStyle) -> Span {
+    style::StyledContent::new(style, text.into())
+}
+
+pub fn spans_width(spans: &[Span]) -> usize {
+    spans.iter().map(|a| width(a.content())).sum()
+}
+
+/// Render the spans as a string with escape codes, using only the colors the terminal supports.
+pub fn render(spans: &[Span], colors: ColorSupport) -> String {
+    spans.iter().map(|a| span(a.content().as_str(), colors.adapt(*a.style())).to_string()).collect()
+}
+
+/// Drop the whitespace at the end of the spans.
+pub fn trim_end(mut spans: Vec<Span>) -> Vec<Span> {
+    while let Some(last) = spans.pop() {
+        let trimmed = last.content().trim_end();
+        if !trimmed.is_empty() {
+            spans.push(span(trimmed, *last.style()));
+            break;
+        }
+    }
+    spans
 }
 
-pub fn unprint(item_count: usize) {
-    execute!(
-        stdout(),
-        cursor::MoveUp(item_count as u16),
+/// Cut the spans to the width, marking the cut with an ellipsis.
+pub fn truncate(spans: Vec<Span>, max_width: usize) -> Vec<Span> {
+    if spans_width(&spans) <= max_width {
+        return spans;
+    }
+    let mut truncated = Vec::new();
+    let mut used = 0;
+    for a in spans {
+        let mut text = String::new();
+        for g in a.content().graphemes(true) {
+            let w = width(g);
+            if used + w + 1 > max_width {
+                text.push('…');
+                truncated.push(span(text, *a.style()));
+                return truncated;
+            }
+            used += w;
+            text.push_str(g);
+        }
+        truncated.push(span(text, *a.style()));
+    }
+    truncated
+}
+
+/// Break the spans into rows of at most the width.
+/// Rows after the first one are indented and don't start with whitespace.
+pub fn wrap(spans: Vec<Span>, max_width: usize, indent: usize) -> Vec<Vec<Span>> {
+    let indent = indent.min(max_width.saturating_sub(1));
+    let mut rows = vec![Vec::new()];
+    let mut used = 0;
+    let mut row_start = false;
+    for a in spans {
+        let mut text = String::new();
+        for g in a.content().graphemes(true) {
+            let w = width(g);
+            if used + w > max_width && used > indent {
+                if !text.is_empty() {
+                    rows.last_mut().unwrap().push(span(text, *a.style()));
+                }
+                rows.push(vec![span(" ".repeat(indent), style::ContentStyle::new())]);
+                text = String::new();
+                used = indent;
+                row_start = true;
+            }
+            if row_start && g.trim().is_empty() {
+                continue;
+            }
+            row_start = false;
+            used += w;
+            text.push_str(g);
+        }
+        rows.last_mut().unwrap().push(span(text, *a.style()));
+    }
+    rows
+}
+
+/// Split the spans at the column.
+/// A wide character across the column is replaced with spaces on both sides.
+pub fn split_at(spans: Vec<Span>, column: usize) -> (Vec<Span>, Vec<Span>) {
+    let mut left = Vec::new();
+    let mut right = Vec::new();
+    let mut used = 0;
+    for a in spans {
+        let mut left_text = String::new();
+        let mut right_text = String::new();
+        for g in a.content().graphemes(true) {
+            let w = width(g);
+            if used + w <= column {
+                left_text.push_str(g);
+            } else if used < column {
+                left_text.push_str(&" ".repeat(column - used));
+                right_text.push_str(&" ".repeat(used + w - column));
+            } else {
+                right_text.push_str(g);
+            }
+            used += w;
+        }
+        if !left_text.is_empty() {
+            left.push(span(left_text, *a.style()));
+        }
+        if !right_text.is_empty() {
+            right.push(span(right_text, *a.style()));
+        }
+    }
+    (left, right)
+}
+
+/// Draw the spans over the row from the column on, padding the row if it is too short.
+pub fn overlay(row: Vec<Span>, column: usize, over: Vec<Span>) -> Vec<Span> {
+    let (mut left, right) = split_at(row, column);
+    let padding = column - spans_width(&left);
+    left.push(span(" ".repeat(padding), style::ContentStyle::new()));
+    let (_, right) = split_at(right, spans_width(&over));
+    left.extend(over);
+    left.extend(right);
+    left
+}
+
+/// Draw the characters at the positions, counted over all the spans, with the style on top of theirs.
+pub fn highlight(spans: Vec<Span>, positions: &[usize], style: style::ContentStyle) -> Vec<Span> {
+    if positions.is_empty() {
+        return spans;
+    }
+    let mut highlighted = Vec::new();
+    let mut index = 0;
+    for a in spans {
+        let mut text = String::new();
+        let mut matched = false;
+        for c in a.content().chars() {
+            let is_match = positions.contains(&index);
+            if is_match != matched && !text.is_empty() {
+                let text_style = if matched { merge(*a.style(), style) } else { *a.style() };
+                highlighted.push(span(std::mem::take(&mut text), text_style));
+            }
+            matched = is_match;
+            text.push(c);
+            index += 1;
+        }
+        if !text.is_empty() {
+            let text_style = if matched { merge(*a.style(), style) } else { *a.style() };
+            highlighted.push(span(text, text_style));
+        }
+    }
+    highlighted
+}
+
+/// Wrap the text to the width between words, one vec of spans per row.
+/// Line breaks in the text start new rows and words too long for a row are split.
+pub fn wrap_words(text: &str, style: style::ContentStyle, max_width: usize) -> Vec<Vec<Span>> {
+    let mut rows = Vec::new();
+    for line in text.lines() {
+        let mut row = String::new();
+        for word in line.split_whitespace() {
+            let joined = if row.is_empty() { word.to_owned() } else { format!("{} {}", row, word) };
+            if width(&joined) <= max_width {
+                row = joined;
+                continue;
+            }
+            if !row.is_empty() {
+                rows.push(vec![span(std::mem::take(&mut row), style)]);
+            }
+            let mut parts = wrap(vec![span(word, style)], max_width, 0);
+            row = parts.pop().unwrap().into_iter().map(|a| a.content().clone()).collect();
+            rows.extend(parts);
+        }
+        rows.push(vec![span(row, style)]);
+    }
+    rows
+}
+
+pub fn unprint(out: &mut impl Write, item_count: usize) {
+    if item_count > 0 {
+        queue!(
+            out,
+            cursor::MoveUp(item_count as u16),
+        ).unwrap();
+    }
+    queue!(
+        out,
+        cursor::MoveToColumn(0),
         terminal::Clear(terminal::ClearType::FromCursorDown)
     ).unwrap()
 }
@@ -54,43 +241,102 @@ pub fn value_valid(value: f64, step: Option<f64>, min: Option<f64>, max: Option<
     true
 }
 
+/// Remove the & in front of the hotkey of a name such as "&Save", && is a plain &.
+/// Only letters and digits can be hotkeys, other & are kept as they are.
+/// Returns the name, and the hotkey in lowercase with its position in the name.
+pub fn parse_mnemonic(name: String) -> (String, Option<(char, usize)>) {
+    if !name.contains('&') {
+        return (name, None);
+    }
+    let mut parsed = String::new();
+    let mut hotkey = None;
+    let mut position = 0;
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('&', Some('&')) => {
+                chars.next();
+            }
+            ('&', Some(next)) if next.is_alphanumeric() && hotkey.is_none() => {
+                hotkey = Some((next.to_lowercase().next().unwrap_or(*next), position));
+                continue;
+            }
+            _ => {}
+        }
+        parsed.push(c);
+        position += 1;
+    }
+    (parsed, hotkey)
+}
+
 pub fn number_range_indicator(step: Option<f64>, min: Option<f64>, max: Option<f64>) -> String {
-    let prefix = String::new();
     if let Some(step) = step {
         if let Some(min) = min {
-            print!("[{:.*}, {:.*}, ..",
+            let mut indicator = format!("[{:.*}, {:.*}, ..",
                    float_printing_precision(min), min,
                    float_printing_precision(min + step), min + step,
             );
             if let Some(max) = max {
-                print!(", {:.*}] ", float_printing_precision(max), max);
+                indicator += &format!(", {:.*}] ", float_printing_precision(max), max);
             } else {
-                print!("] ");
+                indicator += "] ";
             }
+            indicator
         } else if let Some(max) = max {
-            print!("[.., {:.*}, {:.*}] ",
+            format!("[.., {:.*}, {:.*}] ",
                    float_printing_precision(max - step), max - step,
                    float_printing_precision(max), max
-            );
+            )
         } else {
-            print!("[.., {:.*}, 0, {:.*}, ..] ",
+            format!("[.., {:.*}, 0, {:.*}, ..] ",
                    float_printing_precision(-step), -step,
                    float_printing_precision(step), step
-            );
+            )
         }
     } else if let Some(min) = min {
         if let Some(max) = max {
-            print!("[{:.*}..{:.*}] ",
+            format!("[{:.*}..{:.*}] ",
                    float_printing_precision(min), min,
                    float_printing_precision(max), max
-            );
+            )
         } else {
-            print!("[> {:.*}] ", float_printing_precision(min), min);
+            format!("[> {:.*}] ", float_printing_precision(min), min)
         }
     } else if let Some(max) = max {
-        print!("[< {:.*}] ", float_printing_precision(max), max);
+        format!("[< {:.*}] ", float_printing_precision(max), max)
     } else {
-        print!(": ");
+        ": ".to_owned()
     }
-    prefix
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_mnemonic;
+
+    #[test]
+    fn mnemonic_marks_the_next_character() {
+        assert_eq!(parse_mnemonic("&Save".to_owned()), ("Save".to_owned(), Some(('s', 0))));
+        assert_eq!(parse_mnemonic("Save &as".to_owned()), ("Save as".to_owned(), Some(('a', 5))));
+    }
+
+    #[test]
+    fn mnemonic_double_ampersand_is_plain() {
+        assert_eq!(parse_mnemonic("R&&D".to_owned()), ("R&D".to_owned(), None));
+        assert_eq!(parse_mnemonic("&&&Save".to_owned()), ("&Save".to_owned(), Some(('s', 1))));
+    }
+
+    #[test]
+    fn mnemonic_trailing_ampersand_is_kept() {
+        assert_eq!(parse_mnemonic("Save&".to_owned()), ("Save&".to_owned(), None));
+    }
+
+    #[test]
+    fn mnemonic_ampersand_before_space_is_kept() {
+        assert_eq!(parse_mnemonic("Save & quit".to_owned()), ("Save & quit".to_owned(), None));
+    }
+
+    #[test]
+    fn mnemonic_second_marker_is_kept() {
+        assert_eq!(parse_mnemonic("&Save &as".to_owned()), ("Save &as".to_owned(), Some(('s', 0))));
+    }
+}